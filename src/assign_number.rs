@@ -1,54 +1,53 @@
-use zellij_tile::prelude::BareKey;
+use zellij_tile::prelude::KeyWithModifier;
 
-use crate::{favs::Favs, favs_mode::FavMode};
+use crate::{
+    favs::Favs,
+    help::open_help,
+    key_number,
+    keymap::{Action, KeyMatch},
+    status::Severity,
+    text_input::typed_char,
+};
 
-pub fn match_assignation_keys(ctx: &mut Favs, key: &BareKey) -> bool {
+/// Assigns a quick access number with a digit, or a hotkey typed key by key and confirmed.
+pub fn match_assignation_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
     if let Some(column_mode) = ctx.current_column.clone() {
-        match key {
-            BareKey::Char(c) if c.is_ascii_digit() => {
-                let index = c.to_digit(10).unwrap() as u8;
-                let cursor = ctx.cursor;
-                let (mut fav_sessions, mut flush_sessions) = ctx.get_mut_filtered_sessions();
-
-                for session in fav_sessions.iter_mut() {
-                    if let Some(assigned) = session.assigned_number {
-                        if assigned == index {
-                            session.assigned_number = None;
-                        }
-                    }
-                }
-
-                for session in flush_sessions.iter_mut() {
-                    if let Some(assigned) = session.assigned_number {
-                        if assigned == index {
-                            session.assigned_number = None;
-                        }
-                    }
-                }
-
-                match column_mode {
-                    FavMode::NavigateFavs => {
-                        if let Some(session) = fav_sessions.get_mut(cursor) {
-                            session.assigned_number = Some(index);
-                        }
-                    }
-                    FavMode::NavigateFlush => {
-                        if let Some(session) = flush_sessions.get_mut(cursor) {
-                            session.assigned_number = Some(index);
-                        }
-                    }
-                    _ => {}
-                }
-                ctx.mode = column_mode;
-                ctx.current_column = None;
-                ctx.commit_fav_changes();
+        let assigned = match (ctx.resolve_key(key), key_number(key)) {
+            (KeyMatch::Action(Action::Cancel), _) => None,
+            (KeyMatch::Action(Action::InputHelp), _) => {
+                open_help(ctx);
+                return true;
             }
-            BareKey::Esc => {
-                ctx.mode = column_mode;
-                ctx.current_column = None;
+            (KeyMatch::Action(Action::DeleteBack), _) => {
+                ctx.hotkey_input.pop();
+                return true;
             }
+            (KeyMatch::Action(Action::Confirm), _) if !ctx.hotkey_input.is_empty() => {
+                let keys: Vec<String> = ctx.hotkey_input.iter().map(|c| c.to_string()).collect();
+                let hotkey = keys.join(" ");
+                ctx.get_selected_session()
+                    .map(|session| ctx.assign_hotkey(&session.name, Some(&hotkey)))
+            }
+            (KeyMatch::None, Some(number)) if ctx.hotkey_input.is_empty() => ctx
+                .get_selected_session()
+                .map(|session| ctx.assign_number(&session.name, number).map(|_| vec![])),
+            (KeyMatch::None, _) => match typed_char(key) {
+                Some(c) if c != ' ' => {
+                    ctx.hotkey_input.push(c);
+                    return true;
+                }
+                _ => return false,
+            },
             _ => return false,
+        };
+        match assigned {
+            Some(Ok(_)) => ctx.commit_fav_changes(),
+            Some(Err(e)) => ctx.notify(Severity::Error, e),
+            None => {}
         }
+        ctx.mode = column_mode;
+        ctx.current_column = None;
+        ctx.hotkey_input.clear();
     }
     true
 }
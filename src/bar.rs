@@ -0,0 +1,62 @@
+use zellij_tile::prelude::Mouse;
+
+use crate::{
+    favs::Favs,
+    number_label,
+    theme::{Line, Role},
+};
+
+const SEPARATOR: &str = " | ";
+
+pub fn render_bar(ctx: &mut Favs, cols: usize) {
+    ctx.bar_hitboxes.clear();
+
+    let mut line = Line::new();
+
+    for (i, session) in ctx.fav_sessions.iter().enumerate() {
+        let label = format!(" {} ", session.name);
+        let number = session
+            .assigned_number
+            .map(|assigned| format!(" {}", number_label(assigned)))
+            .unwrap_or_default();
+        let separator = if i == 0 { "" } else { SEPARATOR };
+        let label_width = number.chars().count() + label.chars().count();
+
+        if line.len() + separator.len() + label_width > cols {
+            break;
+        }
+
+        line = line.plain(separator);
+        let start = line.len();
+        line = line.styled(number, Role::Number);
+        line = if ctx.current_session.as_ref() == Some(&session.name) {
+            line.styled(label, Role::Title)
+        } else {
+            line.plain(label)
+        };
+
+        ctx.bar_hitboxes
+            .push((start, start + label_width, session.name.clone()));
+    }
+
+    if ctx.fav_sessions.is_empty() {
+        line = line.plain(" No favorites");
+    }
+
+    ctx.theme.print(&line, 0, 0, Some(cols));
+}
+
+pub fn match_bar_mouse(ctx: &mut Favs, mouse: &Mouse) -> bool {
+    if let Mouse::LeftClick(_, col) = mouse {
+        let session = ctx
+            .bar_hitboxes
+            .iter()
+            .find(|(start, end, _)| (*start..*end).contains(col))
+            .map(|(_, _, name)| name.clone());
+        if let Some(name) = session {
+            ctx.switch_to(&name);
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,447 @@
+use crate::{
+    favs::{Favs, FavsJson},
+    number_label, parse_number,
+    sort::SortMode,
+    FavSessionInfo,
+};
+
+/// Names of the commands, in the order they are completed in the command palette.
+pub const COMMAND_NAMES: &[&str] = &[
+    "add", "remove", "assign", "hotkey", "tag", "untag", "note", "rename", "new", "sort", "export",
+    "list", "flush", "back", "next", "prev",
+];
+
+/// A command typed in the command palette or sent through `zellij pipe`. Commands whose session
+/// is left out (`None`) act on the default targets given to [`FavsCommand::run`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum FavsCommand {
+    Add(Option<String>),
+    Remove(Option<String>),
+    Assign(Option<String>, u8),
+    /// A hotkey such as `g a`, or `None` to clear it.
+    Hotkey(Option<String>, Option<String>),
+    Tag(Option<String>, String),
+    Untag(Option<String>, String),
+    Note(String, Option<String>),
+    Rename(Option<String>, String),
+    New(String),
+    Sort(SortMode),
+    Export(Option<String>),
+    List {
+        json: bool,
+    },
+    Flush {
+        dry_run: bool,
+    },
+    Back,
+    Next,
+    Prev,
+}
+
+impl FavsCommand {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let args: Vec<&str> = input.split_whitespace().collect();
+        let Some((command, args)) = args.split_first() else {
+            return Err("empty command".to_string());
+        };
+        let name = |name: &&str| Some(name.to_string());
+
+        match (*command, args) {
+            ("add", []) => Ok(FavsCommand::Add(None)),
+            ("add", [session]) => Ok(FavsCommand::Add(name(session))),
+            ("remove", []) => Ok(FavsCommand::Remove(None)),
+            ("remove", [session]) => Ok(FavsCommand::Remove(name(session))),
+            ("assign", [number]) => Ok(FavsCommand::Assign(None, parse_assigned(number)?)),
+            ("assign", [session, number]) => {
+                Ok(FavsCommand::Assign(name(session), parse_assigned(number)?))
+            }
+            ("hotkey", [keys]) => Ok(FavsCommand::Hotkey(None, parse_hotkey(keys))),
+            ("hotkey", [session, keys]) => {
+                Ok(FavsCommand::Hotkey(name(session), parse_hotkey(keys)))
+            }
+            ("tag", [tag]) => Ok(FavsCommand::Tag(None, tag.to_string())),
+            ("tag", [session, tag]) => Ok(FavsCommand::Tag(name(session), tag.to_string())),
+            ("untag", [tag]) => Ok(FavsCommand::Untag(None, tag.to_string())),
+            ("untag", [session, tag]) => Ok(FavsCommand::Untag(name(session), tag.to_string())),
+            ("note", [session, note @ ..]) => Ok(FavsCommand::Note(
+                session.to_string(),
+                (!note.is_empty()).then(|| note.join(" ")),
+            )),
+            ("rename", [new_name]) => Ok(FavsCommand::Rename(None, new_name.to_string())),
+            ("rename", [session, new_name]) => {
+                Ok(FavsCommand::Rename(name(session), new_name.to_string()))
+            }
+            ("new", [session]) => Ok(FavsCommand::New(session.to_string())),
+            ("sort", [mode]) => match SortMode::parse(mode) {
+                Some(mode) => Ok(FavsCommand::Sort(mode)),
+                None => Err(format!("unknown sort mode '{}'", mode)),
+            },
+            ("export", []) => Ok(FavsCommand::Export(None)),
+            ("export", [path]) => Ok(FavsCommand::Export(Some(path.to_string()))),
+            ("list", []) => Ok(FavsCommand::List { json: false }),
+            ("list", ["--json"]) => Ok(FavsCommand::List { json: true }),
+            ("flush", []) => Ok(FavsCommand::Flush { dry_run: false }),
+            ("flush", ["--dry-run"]) => Ok(FavsCommand::Flush { dry_run: true }),
+            ("back", []) => Ok(FavsCommand::Back),
+            ("next", []) => Ok(FavsCommand::Next),
+            ("prev", []) => Ok(FavsCommand::Prev),
+            ("add" | "remove", _) => Err(format!("usage: {} [session]", command)),
+            ("assign", _) => Err("usage: assign [session] <[alt-|ctrl-|ctrl-alt-]0-9>".to_string()),
+            ("hotkey", _) => Err("usage: hotkey [session] <keys | --clear>".to_string()),
+            ("tag" | "untag", _) => Err(format!("usage: {} [session] <tag>", command)),
+            ("note", _) => Err("usage: note <session> [text]".to_string()),
+            ("rename", _) => Err("usage: rename [session] <new name>".to_string()),
+            ("new", _) => Err("usage: new <name>".to_string()),
+            ("sort", _) => Err("usage: sort <manual | name | mru>".to_string()),
+            ("export", _) => Err("usage: export [path]".to_string()),
+            ("list", _) => Err("usage: list [--json]".to_string()),
+            ("flush", _) => Err("usage: flush [--dry-run]".to_string()),
+            ("back" | "next" | "prev", _) => Err(format!("usage: {}", command)),
+            _ => Err(format!("unknown command '{}'", command)),
+        }
+    }
+
+    /// Runs the command. `targets` are the sessions acted on when the command leaves its session
+    /// out: the marked or highlighted sessions in the palette, the current session from the CLI.
+    pub fn run(self, ctx: &mut Favs, targets: &[String]) -> Result<String, String> {
+        match self {
+            FavsCommand::Add(name) => {
+                let names = resolve(name, targets)?;
+                check_sessions(ctx, &names)?;
+                undoable(ctx, "move to Favorites", |ctx| {
+                    for name in names.iter() {
+                        ctx.move_to_favs(name);
+                    }
+                    Ok(())
+                })?;
+                ctx.commit_fav_changes();
+                Ok(format!("added {} to favorites", describe(&names)))
+            }
+            FavsCommand::Remove(name) => {
+                let names = resolve(name, targets)?;
+                check_sessions(ctx, &names)?;
+                undoable(ctx, "move to Flush", |ctx| {
+                    for name in names.iter() {
+                        ctx.move_to_flush(name);
+                    }
+                    Ok(())
+                })?;
+                ctx.commit_fav_changes();
+                Ok(format!("moved {} to flush", describe(&names)))
+            }
+            FavsCommand::Assign(name, number) => {
+                let name = resolve_one(name, targets)?;
+                let taken_from =
+                    undoable(ctx, format!("assign {}", number_label(number)), |ctx| {
+                        ctx.assign_number(&name, number)
+                    })?;
+                ctx.commit_fav_changes();
+                let message = format!("assigned {} to {}", number_label(number), name);
+                Ok(match taken_from {
+                    Some(taken_from) => format!("{}, taken from {}", message, taken_from),
+                    None => message,
+                })
+            }
+            FavsCommand::Hotkey(name, hotkey) => {
+                let name = resolve_one(name, targets)?;
+                let taken_from = undoable(ctx, "hotkey", |ctx| {
+                    ctx.assign_hotkey(&name, hotkey.as_deref())
+                })?;
+                ctx.commit_fav_changes();
+                let Some(hotkey) = hotkey else {
+                    return Ok(format!("cleared hotkey of {}", name));
+                };
+                let message = format!("assigned hotkey {} to {}", hotkey, name);
+                if taken_from.is_empty() {
+                    return Ok(message);
+                }
+                Ok(format!("{}, taken from {}", message, taken_from.join(", ")))
+            }
+            FavsCommand::Tag(name, tag) => run_tag(ctx, resolve(name, targets)?, &tag, true),
+            FavsCommand::Untag(name, tag) => run_tag(ctx, resolve(name, targets)?, &tag, false),
+            FavsCommand::Note(name, note) => {
+                undoable(ctx, "note", |ctx| {
+                    match ctx.set_notes(&name, note.clone()) {
+                        true => Ok(()),
+                        false => Err(format!("session '{}' not found", name)),
+                    }
+                })?;
+                ctx.commit_fav_changes();
+                match note {
+                    Some(_) => Ok(format!("saved note for {}", name)),
+                    None => Ok(format!("cleared note for {}", name)),
+                }
+            }
+            FavsCommand::Rename(name, new_name) => {
+                let name = resolve_one(name, targets)?;
+                ctx.rename_session(&name, &new_name)?;
+                Ok(format!("renamed {} to {}", name, new_name))
+            }
+            FavsCommand::New(name) => {
+                let exists = ctx.find_session(&name).is_some();
+                ctx.switch_to(&name);
+                if exists {
+                    Ok(format!("switched to {}", name))
+                } else {
+                    Ok(format!("created {}", name))
+                }
+            }
+            FavsCommand::Sort(mode) => {
+                ctx.sort_mode = mode;
+                Ok(format!("sorted by {}", mode))
+            }
+            FavsCommand::Export(path) => {
+                let sessions: Vec<FavSessionInfo> = if ctx.marked.is_empty() {
+                    ctx.fav_sessions
+                        .iter()
+                        .chain(ctx.flush_sessions.iter())
+                        .cloned()
+                        .collect()
+                } else {
+                    ctx.target_sessions()
+                };
+                let path = path.unwrap_or_else(|| ctx.export_path.clone());
+                ctx.export_sessions(&sessions, &path);
+                Ok(format!("exporting {} sessions to {}", sessions.len(), path))
+            }
+            FavsCommand::List { json } => {
+                if json {
+                    let favs_info: FavsJson = (&*ctx).into();
+                    return serde_json::to_string(&favs_info).map_err(|e| e.to_string());
+                }
+                let mut output = String::from("Favorites\n");
+                output.push_str(&list_sessions(&ctx.fav_sessions));
+                output.push_str("Flush\n");
+                output.push_str(&list_sessions(&ctx.flush_sessions));
+                Ok(output)
+            }
+            FavsCommand::Flush { dry_run } => {
+                let sessions = ctx.flush_sessions.clone();
+                let names: Vec<&str> = sessions.iter().map(|s| s.name.as_str()).collect();
+                if dry_run {
+                    return Ok(format!("would flush: {}", names.join(" ")));
+                }
+                ctx.push_undo_with_note(
+                    format!("flush of {} sessions", sessions.len()),
+                    "deleted sessions can't be brought back",
+                );
+                ctx.marked.clear();
+                ctx.delete_sessions(&sessions);
+                ctx.commit_fav_changes();
+                Ok(format!("flushed: {}", names.join(" ")))
+            }
+            FavsCommand::Back => ctx.go_back().map(|name| format!("switched to {}", name)),
+            FavsCommand::Next => ctx
+                .cycle_favs(true)
+                .map(|name| format!("switched to {}", name)),
+            FavsCommand::Prev => ctx
+                .cycle_favs(false)
+                .map(|name| format!("switched to {}", name)),
+        }
+    }
+}
+
+fn parse_assigned(number: &str) -> Result<u8, String> {
+    parse_number(number).ok_or_else(|| {
+        format!(
+            "invalid quick access number '{}', expected e.g. 3, alt-3, ctrl-3 or ctrl-alt-3",
+            number
+        )
+    })
+}
+
+/// Hotkeys are written without spaces, one char per key, e.g. `ga` for `g a`.
+fn parse_hotkey(keys: &str) -> Option<String> {
+    if keys == "--clear" {
+        return None;
+    }
+    let keys: Vec<String> = keys.chars().map(|c| c.to_string()).collect();
+    Some(keys.join(" "))
+}
+
+/// The session named in the command, or the default targets when it was left out.
+fn resolve(name: Option<String>, targets: &[String]) -> Result<Vec<String>, String> {
+    match name {
+        Some(name) => Ok(vec![name]),
+        None if targets.is_empty() => Err("no session given".to_string()),
+        None => Ok(targets.to_vec()),
+    }
+}
+
+fn resolve_one(name: Option<String>, targets: &[String]) -> Result<String, String> {
+    match resolve(name, targets)?.as_slice() {
+        [name] => Ok(name.clone()),
+        _ => Err("this command takes a single session, unmark the others".to_string()),
+    }
+}
+
+/// Fails on the first session that doesn't exist, before a command changes any of them.
+fn check_sessions(ctx: &Favs, names: &[String]) -> Result<(), String> {
+    match names.iter().find(|name| ctx.find_session(name).is_none()) {
+        Some(name) => Err(format!("session '{}' not found", name)),
+        None => Ok(()),
+    }
+}
+
+/// Runs a change as one undo step, like the same change made with a key. The step is dropped
+/// when the change fails and the marks are cleared when it succeeds.
+fn undoable<T, S: ToString>(
+    ctx: &mut Favs,
+    description: S,
+    change: impl FnOnce(&mut Favs) -> Result<T, String>,
+) -> Result<T, String> {
+    ctx.push_undo(description);
+    let result = change(ctx);
+    match result {
+        Ok(_) => ctx.marked.clear(),
+        Err(_) => {
+            ctx.undo_stack.pop();
+        }
+    }
+    result
+}
+
+fn describe(names: &[String]) -> String {
+    match names {
+        [name] => name.clone(),
+        names => format!("{} sessions", names.len()),
+    }
+}
+
+fn run_tag(ctx: &mut Favs, names: Vec<String>, tag: &str, enabled: bool) -> Result<String, String> {
+    let tag = tag.trim_start_matches('#');
+    check_sessions(ctx, &names)?;
+    undoable(ctx, format!("tag #{}", tag), |ctx| {
+        for name in names.iter() {
+            ctx.set_tag(name, tag, enabled);
+        }
+        Ok(())
+    })?;
+    ctx.commit_fav_changes();
+    if enabled {
+        Ok(format!("tagged {} with #{}", describe(&names), tag))
+    } else {
+        Ok(format!("removed #{} from {}", tag, describe(&names)))
+    }
+}
+
+fn list_sessions(sessions: &[FavSessionInfo]) -> String {
+    sessions
+        .iter()
+        .map(|session| {
+            let mut line = format!("  {}", session.name);
+            if let Some(number) = session.assigned_number {
+                line.push_str(&format!(" ({})", number_label(number)));
+            }
+            if let Some(hotkey) = &session.hotkey {
+                line.push_str(&format!(" [{}]", hotkey));
+            }
+            line + "\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<FavsCommand, String> {
+        FavsCommand::parse(input)
+    }
+
+    #[test]
+    fn parses_optional_sessions() {
+        assert_eq!(parse("add"), Ok(FavsCommand::Add(None)));
+        assert_eq!(
+            parse(" add  api "),
+            Ok(FavsCommand::Add(Some("api".into())))
+        );
+        assert_eq!(
+            parse("untag api old"),
+            Ok(FavsCommand::Untag(Some("api".into()), "old".into()))
+        );
+        assert_eq!(
+            parse("rename web"),
+            Ok(FavsCommand::Rename(None, "web".into()))
+        );
+    }
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(parse("list"), Ok(FavsCommand::List { json: false }));
+        assert_eq!(parse("list --json"), Ok(FavsCommand::List { json: true }));
+        assert_eq!(parse("flush"), Ok(FavsCommand::Flush { dry_run: false }));
+        assert_eq!(
+            parse("flush --dry-run"),
+            Ok(FavsCommand::Flush { dry_run: true })
+        );
+        assert!(parse("list --yaml").is_err());
+        assert!(parse("flush --force").is_err());
+    }
+
+    #[test]
+    fn parses_numbers_in_every_bank() {
+        assert_eq!(parse("assign 3"), Ok(FavsCommand::Assign(None, 3)));
+        assert_eq!(
+            parse("assign api alt-3"),
+            Ok(FavsCommand::Assign(Some("api".into()), 13))
+        );
+        assert_eq!(
+            parse("assign ctrl-alt-0"),
+            Ok(FavsCommand::Assign(None, 30))
+        );
+        assert!(parse("assign api 10").unwrap_err().contains("'10'"));
+        assert!(parse("assign shift-3").is_err());
+    }
+
+    #[test]
+    fn parses_hotkeys_one_char_per_key() {
+        assert_eq!(
+            parse("hotkey ga"),
+            Ok(FavsCommand::Hotkey(None, Some("g a".into())))
+        );
+        assert_eq!(
+            parse("hotkey api --clear"),
+            Ok(FavsCommand::Hotkey(Some("api".into()), None))
+        );
+        assert_eq!(parse("hotkey --clear"), Ok(FavsCommand::Hotkey(None, None)));
+    }
+
+    #[test]
+    fn joins_note_text() {
+        assert_eq!(
+            parse("note api deploy  on friday"),
+            Ok(FavsCommand::Note(
+                "api".into(),
+                Some("deploy on friday".into())
+            ))
+        );
+        assert_eq!(parse("note api"), Ok(FavsCommand::Note("api".into(), None)));
+    }
+
+    #[test]
+    fn reports_usage_errors() {
+        assert_eq!(parse(""), Err("empty command".to_string()));
+        assert_eq!(parse("  "), Err("empty command".to_string()));
+        assert_eq!(
+            parse("frobnicate"),
+            Err("unknown command 'frobnicate'".to_string())
+        );
+        assert_eq!(parse("add a b"), Err("usage: add [session]".to_string()));
+        assert_eq!(parse("tag"), Err("usage: tag [session] <tag>".to_string()));
+        assert_eq!(
+            parse("note"),
+            Err("usage: note <session> [text]".to_string())
+        );
+        assert_eq!(
+            parse("hotkey a b c"),
+            Err("usage: hotkey [session] <keys | --clear>".to_string())
+        );
+        assert_eq!(parse("back now"), Err("usage: back".to_string()));
+        assert_eq!(
+            parse("sort random"),
+            Err("unknown sort mode 'random'".to_string())
+        );
+        assert_eq!(parse("new"), Err("usage: new <name>".to_string()));
+    }
+}
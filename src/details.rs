@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use crate::{
+    favs::Favs,
+    layout::Rect,
+    theme::{Line, Role},
+    FavSessionInfo,
+};
+
+pub fn format_age(age: &Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+fn details_lines(ctx: &Favs, session: &FavSessionInfo) -> Vec<Line> {
+    let mut lines = vec![];
+
+    let status = if session.is_active {
+        let clients = match session.connected_clients {
+            1 => "1 client".to_string(),
+            n => format!("{} clients", n),
+        };
+        format!("active, {}", clients)
+    } else {
+        match ctx.resurrectable_ages.get(&session.name) {
+            Some(age) => format!("resurrectable, {} ago", format_age(age)),
+            None => "resurrectable".to_string(),
+        }
+    };
+    lines.push(
+        Line::new()
+            .styled(&session.name, Role::Title)
+            .plain(format!(" ({})", status)),
+    );
+
+    if !session.tags.is_empty() {
+        let tags: Vec<String> = session.tags.iter().map(|t| format!("#{}", t)).collect();
+        lines.push(Line::new().styled(tags.join(" "), Role::Match));
+    }
+    if let Some(notes) = &session.notes {
+        lines.push(Line::new().styled("Notes: ", Role::Section).plain(notes));
+    }
+
+    if session.tab_list.is_empty() {
+        lines.push(Line::new().plain("No tabs known yet"));
+    }
+    for (i, tab) in session.tab_list.iter().enumerate() {
+        lines.push(
+            Line::new()
+                .styled(format!("{} ", i + 1), Role::Number)
+                .styled(&tab.name, Role::Section),
+        );
+        for pane in tab.panes.iter() {
+            let label = pane.command.as_ref().unwrap_or(&pane.title);
+            let line = Line::new().plain(format!("  {}", label));
+            lines.push(match (pane.exited, pane.exit_status) {
+                (true, Some(code)) if code != 0 => {
+                    line.styled(format!(" (exit {})", code), Role::Error)
+                }
+                (true, code) => line.plain(format!(" (exit {})", code.unwrap_or(0))),
+                _ => line,
+            });
+        }
+    }
+    lines
+}
+
+pub fn render_details(ctx: &Favs, rect: Rect) {
+    let Some(session) = ctx.get_selected_session() else {
+        return;
+    };
+    if rect.height == 0 {
+        return;
+    }
+    let separator = Line::new().plain("─".repeat(rect.width));
+    ctx.theme
+        .print(&separator, rect.x, rect.y, Some(rect.width));
+
+    for (i, line) in details_lines(ctx, &session)
+        .iter()
+        .take(rect.height - 1)
+        .enumerate()
+    {
+        ctx.theme
+            .print(line, rect.x, rect.y + 1 + i, Some(rect.width));
+    }
+}
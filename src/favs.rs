@@ -1,28 +1,105 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, Instant},
+};
 
-use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 use zellij_tile::{
     prelude::*,
-    shim::{print_text_with_coordinates, request_permission, subscribe, Text},
+    shim::{delete_dead_session, kill_sessions, request_permission, subscribe},
     ZellijPlugin,
 };
 
 use crate::{
-    assign_number::match_assignation_keys, favs_mode::FavMode, filter::match_filter_key,
-    help::match_help_keys, navigate::match_navigation_keys, FavSessionInfo, FavsCommandType,
+    assign_number::match_assignation_keys,
+    bar::{match_bar_mouse, render_bar},
+    details::render_details,
+    favs_mode::FavMode,
+    filter::{match_filter_key, match_filter_paste},
+    help::{match_help_keys, render_help, HelpView},
+    jump::{jump_label, match_jump_keys},
+    key_number,
+    keymap::{bindings_overlap, normalize_key, parse_binding, Action, KeyMatch, Keymap},
+    layout::{sessions_space, NavigationLayout, Rect},
+    mouse::match_navigation_mouse,
+    navigate::{match_navigation_keys, ColumnCursor},
+    number_label,
+    palette::{match_palette_keys, match_palette_paste, palette_footer, palette_line},
+    pipe::match_pipe_message,
+    presets::{match_preset_keys, render_presets},
+    query::Query,
+    reload::{defer, run_deferred, Deferred},
+    renames::{close_rename_questions, detect_renames, match_rename_question_keys, RenameGuess},
+    shell_path, shell_quote,
+    sort::{unix_now, SortMode},
+    status::{Severity, Status},
+    text_input::TextInput,
+    theme::{Line, Role, Theme},
+    ui_state::{UiState, UI_SAVE_DELAY},
+    undo::{restore, UndoStep, MAX_UNDO_STEPS},
+    FavSessionInfo, FavsCommandType, FavsDisplay,
 };
 
 pub struct Favs {
     pub fav_sessions: Vec<FavSessionInfo>,
     pub flush_sessions: Vec<FavSessionInfo>,
-    pub cursor: usize,
+    pub marked: BTreeSet<String>,
+    pub undo_stack: Vec<UndoStep>,
+    pub tag_input: Option<TextInput>,
+    pub rename_input: Option<TextInput>,
+    /// Renames sent to Zellij, from the old to the new name, until a session update reports
+    /// the new name.
+    pub pending_renames: BTreeMap<String, String>,
+    /// Renames detected in session updates that matched several sessions, waiting for the user
+    /// to confirm them.
+    pub rename_questions: Vec<RenameGuess>,
+    pub export_path: String,
+    pub fav_cursor: ColumnCursor,
+    pub flush_cursor: ColumnCursor,
+    pub keymap: Keymap,
+    pub pending_keys: Vec<KeyWithModifier>,
     pub mode: FavMode,
     pub current_column: Option<FavMode>,
-    pub filter: Option<String>,
+    /// Keys typed in assign mode for a hotkey, e.g. `['g', 'a']`.
+    pub hotkey_input: Vec<char>,
+    /// Labels shown next to the visible sessions while jumping, with the session they open.
+    pub jump_labels: Vec<(String, String)>,
+    pub jump_input: String,
+    pub filter: Option<TextInput>,
+    pub sort_mode: SortMode,
+    /// Set when `sort` is configured, which then wins over the sort saved with the UI state.
+    pub sort_configured: bool,
+    pub command_input: Option<TextInput>,
+    pub completions: Vec<String>,
+    pub filter_history: Vec<String>,
+    pub history_index: Option<usize>,
+    pub presets: BTreeMap<String, String>,
+    pub config_presets: BTreeMap<String, String>,
+    pub preset_cursor: usize,
+    pub preset_name: Option<TextInput>,
+    pub help: Option<HelpView>,
     pub has_loaded: bool,
     pub cache_dir: String,
     pub display_tab_panes: bool,
+    pub search_contents: bool,
+    pub theme: Theme,
+    pub show_details: bool,
+    /// Saved UI state per session name, or under `""` when it's shared by all sessions.
+    pub ui_states: BTreeMap<String, UiState>,
+    pub restore_ui: bool,
+    pub ui_state_per_session: bool,
+    /// When the UI state last changed without being saved.
+    pub ui_changed_at: Option<Instant>,
+    pub resurrectable_ages: BTreeMap<String, Duration>,
+    /// Pipe commands and switches waiting for the cache to be read again.
+    pub deferred: Vec<Deferred>,
+    pub status: Option<Status>,
+    pub current_session: Option<String>,
+    pub display: FavsDisplay,
+    pub bar_hitboxes: Vec<(usize, usize, String)>,
+    pub last_click: Option<(Instant, String)>,
+    pub rows: usize,
+    pub cols: usize,
 }
 
 impl Default for Favs {
@@ -30,13 +107,53 @@ impl Default for Favs {
         Self {
             has_loaded: false,
             fav_sessions: vec![],
-            cursor: 0,
+            marked: BTreeSet::new(),
+            undo_stack: vec![],
+            tag_input: None,
+            rename_input: None,
+            pending_renames: BTreeMap::new(),
+            rename_questions: vec![],
+            export_path: String::from("~/.cache/favs-export.json"),
+            fav_cursor: ColumnCursor::default(),
+            flush_cursor: ColumnCursor::default(),
+            keymap: Keymap::default(),
+            pending_keys: vec![],
             mode: FavMode::NavigateFavs,
             current_column: None,
+            hotkey_input: vec![],
+            jump_labels: vec![],
+            jump_input: String::new(),
             filter: None,
+            sort_mode: SortMode::default(),
+            sort_configured: false,
+            command_input: None,
+            completions: vec![],
+            filter_history: vec![],
+            history_index: None,
+            presets: BTreeMap::new(),
+            config_presets: BTreeMap::new(),
+            preset_cursor: 0,
+            preset_name: None,
+            help: None,
             flush_sessions: vec![],
             cache_dir: String::from("~/.cache/favs.json"),
             display_tab_panes: false,
+            search_contents: false,
+            theme: Theme::default(),
+            show_details: false,
+            ui_states: BTreeMap::new(),
+            restore_ui: true,
+            ui_state_per_session: false,
+            ui_changed_at: None,
+            resurrectable_ages: BTreeMap::new(),
+            deferred: vec![],
+            status: None,
+            current_session: None,
+            display: FavsDisplay::Manager,
+            bar_hitboxes: vec![],
+            last_click: None,
+            rows: 0,
+            cols: 0,
         }
     }
 }
@@ -45,6 +162,10 @@ impl Default for Favs {
 pub struct FavsJson {
     pub favs: Vec<FavSessionInfo>,
     pub flush: Vec<FavSessionInfo>,
+    #[serde(default)]
+    pub presets: BTreeMap<String, String>,
+    #[serde(default)]
+    pub ui_state: BTreeMap<String, UiState>,
 }
 
 impl From<&Favs> for FavsJson {
@@ -52,20 +173,149 @@ impl From<&Favs> for FavsJson {
         FavsJson {
             favs: val.fav_sessions.clone(),
             flush: val.flush_sessions.clone(),
+            presets: val.presets.clone(),
+            ui_state: val.ui_states_to_save(),
         }
     }
 }
 
 impl Favs {
-    fn match_key(&mut self, key: &BareKey) -> bool {
+    fn match_key(&mut self, key: &KeyWithModifier) -> bool {
         match &mut self.mode {
             FavMode::Filter => match_filter_key(self, key),
             FavMode::Help => match_help_keys(self, key),
             FavMode::AssignNumber => match_assignation_keys(self, key),
+            FavMode::Presets => match_preset_keys(self, key),
+            FavMode::Command => match_palette_keys(self, key),
+            FavMode::Jump => match_jump_keys(self, key),
+            FavMode::Renames => match_rename_question_keys(self, key),
             _ => match_navigation_keys(self, key),
         }
     }
+    /// Resolves a key against the bindings of the current mode, remembering the keys typed so
+    /// far while they could still complete a longer binding such as `g g`.
+    pub fn resolve_key(&mut self, key: &KeyWithModifier) -> KeyMatch {
+        let key = normalize_key(key);
+        let mut keys = std::mem::take(&mut self.pending_keys);
+        keys.push(key.clone());
+        let mut key_match = self.resolve_keys(&keys);
+        if matches!(key_match, KeyMatch::None) && keys.len() > 1 {
+            keys = vec![key];
+            key_match = self.resolve_keys(&keys);
+        }
+        if let KeyMatch::Pending = key_match {
+            self.pending_keys = keys;
+        }
+        key_match
+    }
+    /// Bindings of the current mode, then the session hotkeys while navigating.
+    fn resolve_keys(&self, keys: &[KeyWithModifier]) -> KeyMatch {
+        let key_match = self.keymap.resolve(&self.mode, keys);
+        if !matches!(key_match, KeyMatch::None)
+            || !matches!(self.mode, FavMode::NavigateFavs | FavMode::NavigateFlush)
+        {
+            return key_match;
+        }
+        let mut pending = false;
+        for session in self.fav_sessions.iter().chain(self.flush_sessions.iter()) {
+            let Some(Ok(hotkey)) = session.hotkey.as_deref().map(parse_binding) else {
+                continue;
+            };
+            if hotkey.as_slice() == keys {
+                return KeyMatch::Hotkey(session.name.clone());
+            }
+            pending |= hotkey.starts_with(keys);
+        }
+        if pending {
+            KeyMatch::Pending
+        } else {
+            KeyMatch::None
+        }
+    }
+    fn match_mouse(&mut self, mouse: &Mouse) -> bool {
+        match self.display {
+            FavsDisplay::Bar => match_bar_mouse(self, mouse),
+            FavsDisplay::Manager => match_navigation_mouse(self, mouse),
+        }
+    }
+    pub fn get_layout(&self) -> NavigationLayout {
+        NavigationLayout::new(self.rows, self.cols, self.show_details)
+    }
+    /// The column the cursor keys act on.
+    pub fn active_column(&self) -> FavMode {
+        match self.mode {
+            FavMode::AssignNumber | FavMode::Command | FavMode::Jump | FavMode::Renames => {
+                self.current_column.clone().unwrap_or(FavMode::NavigateFavs)
+            }
+            FavMode::NavigateFlush => FavMode::NavigateFlush,
+            _ => FavMode::NavigateFavs,
+        }
+    }
+    pub fn column_cursor(&self, column: &FavMode) -> &ColumnCursor {
+        match column {
+            FavMode::NavigateFlush => &self.flush_cursor,
+            _ => &self.fav_cursor,
+        }
+    }
+    pub fn column_cursor_mut(&mut self, column: &FavMode) -> &mut ColumnCursor {
+        match column {
+            FavMode::NavigateFlush => &mut self.flush_cursor,
+            _ => &mut self.fav_cursor,
+        }
+    }
+    pub fn get_selected_session(&self) -> Option<FavSessionInfo> {
+        if matches!(self.mode, FavMode::Help | FavMode::Presets) {
+            return None;
+        }
+        let (fav_sessions, flush_sessions) = self.get_filtered_sessions();
+        let column = self.active_column();
+        let index = self.column_cursor(&column).index;
+        match column {
+            FavMode::NavigateFlush => flush_sessions.get(index).cloned(),
+            _ => fav_sessions.get(index).cloned(),
+        }
+    }
+    /// Moves the cursor of the active column by `delta` rows, stopping at either end.
+    pub fn move_cursor(&mut self, delta: isize) {
+        let (fav_sessions, flush_sessions) = self.get_filtered_sessions();
+        let column = self.active_column();
+        let len = match column {
+            FavMode::NavigateFlush => flush_sessions.len(),
+            _ => fav_sessions.len(),
+        };
+        let cursor = self.column_cursor_mut(&column);
+        cursor.index = cursor
+            .index
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+    /// Number of session rows visible in the active column.
+    pub fn page_size(&self) -> usize {
+        sessions_space(&self.get_layout().column(&self.active_column())).max(1)
+    }
+    /// Brings each column's scroll offset in line with its cursor and the current pane size.
+    fn scroll_columns(&mut self) {
+        let layout = self.get_layout();
+        let (fav_sessions, flush_sessions) = self.get_filtered_sessions();
+        self.fav_cursor
+            .scroll(fav_sessions.len(), sessions_space(&layout.favs));
+        self.flush_cursor
+            .scroll(flush_sessions.len(), sessions_space(&layout.flush));
+    }
     pub fn commit_fav_changes(&self) {
+        self.write_cache(false);
+    }
+    /// Saves the cache without notifying, for writes that don't come from an edit by the user:
+    /// session updates, last-used times and the UI state.
+    pub fn save_quietly(&mut self) {
+        self.ui_changed_at = None;
+        self.write_cache(true);
+    }
+    fn write_cache(&self, quiet: bool) {
+        // The bar only shows what the manager saved, its copy may be behind.
+        if self.display == FavsDisplay::Bar {
+            return;
+        }
         let favs_info: FavsJson = self.into();
         let json = serde_json::to_string(&favs_info).unwrap();
         let mut data = BTreeMap::new();
@@ -73,246 +323,840 @@ impl Favs {
             FavsCommandType::get_command_key(),
             FavsCommandType::WriteCache.to_string(),
         );
+        if quiet {
+            data.insert("quiet".to_string(), "true".to_string());
+        }
 
         run_command(
             &[
                 "bash",
                 "-c",
-                format!("echo '{}' > {}", json, self.cache_dir).as_str(),
+                format!(
+                    "cp {0} {0}.bak 2>/dev/null; echo {1} > {0}",
+                    shell_path(&self.cache_dir),
+                    shell_quote(&json)
+                )
+                .as_str(),
             ],
             data,
         );
     }
-    pub fn get_mut_filtered_sessions(
+    /// Shows a transient message, unless a more severe one is still visible.
+    pub fn notify<S: ToString>(&mut self, severity: Severity, message: S) {
+        if self
+            .status
+            .as_ref()
+            .is_some_and(|status| !status.is_expired() && status.severity > severity)
+        {
+            return;
+        }
+        self.status = Some(Status::new(severity, message));
+    }
+    fn expire_status(&mut self) -> bool {
+        if self
+            .status
+            .as_ref()
+            .is_some_and(|status| status.is_expired())
+        {
+            self.status = None;
+            return true;
+        }
+        false
+    }
+    pub fn find_session(&self, name: &str) -> Option<&FavSessionInfo> {
+        self.fav_sessions
+            .iter()
+            .chain(self.flush_sessions.iter())
+            .find(|session| session.name == name)
+    }
+    pub fn find_session_mut(&mut self, name: &str) -> Option<&mut FavSessionInfo> {
+        self.fav_sessions
+            .iter_mut()
+            .chain(self.flush_sessions.iter_mut())
+            .find(|session| session.name == name)
+    }
+    pub fn move_to_flush(&mut self, name: &str) -> bool {
+        let Some(session_idx) = self.fav_sessions.iter().position(|s| s.name == name) else {
+            return false;
+        };
+        let session = self.fav_sessions.remove(session_idx);
+        self.flush_sessions.push(session);
+        true
+    }
+    pub fn move_to_favs(&mut self, name: &str) -> bool {
+        let Some(session_idx) = self.flush_sessions.iter().position(|s| s.name == name) else {
+            return false;
+        };
+        let session = self.flush_sessions.remove(session_idx);
+        self.fav_sessions.push(session);
+        true
+    }
+    /// Gives `number` to the session, taking it from the session that had it, with a warning.
+    /// Returns the session it was taken from.
+    pub fn assign_number(&mut self, name: &str, number: u8) -> Result<Option<String>, String> {
+        if self.find_session(name).is_none() {
+            return Err(format!("session '{}' not found", name));
+        }
+        let mut taken_from = None;
+        for session in self
+            .fav_sessions
+            .iter_mut()
+            .chain(self.flush_sessions.iter_mut())
+        {
+            if session.assigned_number == Some(number) && session.name != name {
+                session.assigned_number = None;
+                taken_from = Some(session.name.clone());
+            }
+            if session.name == name {
+                session.assigned_number = Some(number);
+            }
+        }
+        if let Some(taken_from) = &taken_from {
+            self.notify(
+                Severity::Warning,
+                format!(
+                    "{} moved from {} to {}",
+                    number_label(number),
+                    taken_from,
+                    name
+                ),
+            );
+        }
+        Ok(taken_from)
+    }
+    /// Gives the session a hotkey such as `g a`, or clears it with `None`. Hotkeys that are the
+    /// same, or start one another, are taken from the sessions that had them, with a warning.
+    /// Returns the sessions they were taken from.
+    pub fn assign_hotkey(
         &mut self,
-    ) -> (Vec<&mut FavSessionInfo>, Vec<&mut FavSessionInfo>) {
-        if let Some(filter) = self.filter.clone() {
-            return (
-                self.fav_sessions
-                    .iter_mut()
-                    .filter(|session| session.name.to_lowercase().contains(&filter.to_lowercase()))
-                    .collect(),
+        name: &str,
+        hotkey: Option<&str>,
+    ) -> Result<Vec<String>, String> {
+        if self.find_session(name).is_none() {
+            return Err(format!("session '{}' not found", name));
+        }
+        let Some(hotkey) = hotkey else {
+            if let Some(session) = self.find_session_mut(name) {
+                session.hotkey = None;
+            }
+            return Ok(vec![]);
+        };
+        let keys = parse_binding(hotkey)?;
+        if key_number(&keys[0]).is_some() {
+            return Err(
+                "hotkeys can't start with a digit, used by quick access numbers".to_string(),
+            );
+        }
+        if let Some(action) = self.keymap.navigation_conflict(&keys) {
+            return Err(format!(
+                "'{}' clashes with the {} binding",
+                hotkey,
+                action.name()
+            ));
+        }
+        let mut taken_from = vec![];
+        for session in self
+            .fav_sessions
+            .iter_mut()
+            .chain(self.flush_sessions.iter_mut())
+        {
+            if session.name == name {
+                session.hotkey = Some(hotkey.to_string());
+                continue;
+            }
+            let clashes = session
+                .hotkey
+                .as_deref()
+                .and_then(|other| parse_binding(other).ok())
+                .is_some_and(|other| bindings_overlap(&other, &keys));
+            if clashes {
+                session.hotkey = None;
+                taken_from.push(session.name.clone());
+            }
+        }
+        if !taken_from.is_empty() {
+            self.notify(
+                Severity::Warning,
+                format!("hotkey {} taken from {}", hotkey, taken_from.join(", ")),
+            );
+        }
+        Ok(taken_from)
+    }
+    pub fn set_tag(&mut self, name: &str, tag: &str, enabled: bool) -> bool {
+        let Some(session) = self.find_session_mut(name) else {
+            return false;
+        };
+        session.tags.retain(|t| t != tag);
+        if enabled {
+            session.tags.push(tag.to_string());
+        }
+        true
+    }
+    pub fn set_notes(&mut self, name: &str, notes: Option<String>) -> bool {
+        let Some(session) = self.find_session_mut(name) else {
+            return false;
+        };
+        session.notes = notes;
+        true
+    }
+    /// Why `name` can't be renamed from here, if it can't.
+    pub fn rename_blocker(&self, name: &str) -> Option<String> {
+        if self.find_session(name).is_none() {
+            return Some(format!("session '{}' not found", name));
+        }
+        if self.current_session.as_deref() != Some(name) {
+            // Zellij only lets a plugin rename the session it runs in.
+            return Some(format!(
+                "only the current session can be renamed, switch to {} first",
+                name
+            ));
+        }
+        None
+    }
+    /// Renames the current session, keeping its list, number, tags, notes and marks. The entry
+    /// is renamed right away and remembered in `pending_renames` so session updates sent before
+    /// Zellij applies the rename don't treat it as a new session.
+    pub fn rename_session(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        if let Some(reason) = self.rename_blocker(name) {
+            return Err(reason);
+        }
+        if new_name.is_empty() || new_name.contains('/') {
+            return Err(format!("invalid session name '{}'", new_name));
+        }
+        if self.find_session(new_name).is_some() {
+            return Err(format!("session '{}' already exists", new_name));
+        }
+        rename_session(new_name);
+        self.rename_entry(name, new_name);
+        self.pending_renames
+            .insert(name.to_string(), new_name.to_string());
+        self.commit_fav_changes();
+        Ok(())
+    }
+    /// Moves everything the plugin knows about `name` over to `new_name`.
+    fn rename_entry(&mut self, name: &str, new_name: &str) {
+        let rename = |session: &mut FavSessionInfo| {
+            if session.name == name {
+                session.name = new_name.to_string();
+            }
+        };
+        self.fav_sessions.iter_mut().for_each(rename);
+        self.flush_sessions.iter_mut().for_each(rename);
+        for step in self.undo_stack.iter_mut() {
+            step.fav_sessions.iter_mut().for_each(rename);
+            step.flush_sessions.iter_mut().for_each(rename);
+        }
+        if self.current_session.as_deref() == Some(name) {
+            self.current_session = Some(new_name.to_string());
+        }
+        if self.marked.remove(name) {
+            self.marked.insert(new_name.to_string());
+        }
+    }
+    /// Entries of both lists that none of the `reported` sessions belongs to.
+    fn gone_sessions(&self, reported: &BTreeSet<String>) -> Vec<RenameGuess> {
+        let entries = self
+            .fav_sessions
+            .iter()
+            .enumerate()
+            .map(|(index, session)| (true, index, session))
+            .chain(
                 self.flush_sessions
-                    .iter_mut()
-                    .filter(|session| session.name.to_lowercase().contains(&filter.to_lowercase()))
-                    .collect(),
+                    .iter()
+                    .enumerate()
+                    .map(|(index, session)| (false, index, session)),
             );
+        entries
+            .filter(|(_, _, session)| {
+                !reported
+                    .iter()
+                    .any(|name| self.is_entry_of(&session.name, name))
+            })
+            .map(|(was_fav, index, session)| RenameGuess {
+                previous: session.clone(),
+                was_fav,
+                index,
+                new_name: String::new(),
+            })
+            .collect()
+    }
+    /// Gives the session now called `guess.new_name` the metadata and place of its old entry.
+    fn apply_rename(&mut self, guess: &RenameGuess) {
+        let session = if let Some(idx) = self
+            .flush_sessions
+            .iter()
+            .position(|s| s.name == guess.new_name)
+        {
+            self.flush_sessions.remove(idx)
+        } else if let Some(idx) = self
+            .fav_sessions
+            .iter()
+            .position(|s| s.name == guess.new_name)
+        {
+            self.fav_sessions.remove(idx)
+        } else {
+            return;
+        };
+        let session = session.with_metadata(&guess.previous);
+        let list = if guess.was_fav {
+            &mut self.fav_sessions
+        } else {
+            &mut self.flush_sessions
+        };
+        list.insert(guess.index.min(list.len()), session);
+        self.rename_entry(&guess.previous.name, &guess.new_name);
+    }
+    /// Answers the first rename question, carrying the metadata over when `accept` is true.
+    pub fn answer_rename_question(&mut self, accept: bool) {
+        if self.rename_questions.is_empty() {
+            return;
         }
+        let guess = self.rename_questions.remove(0);
+        if !accept {
+            return;
+        }
+        self.apply_rename(&guess);
+        self.rename_questions.retain(|other| {
+            other.previous.name != guess.previous.name && other.new_name != guess.new_name
+        });
+        self.notify(
+            Severity::Success,
+            format!(
+                "{} keeps the metadata of {}",
+                guess.new_name, guess.previous.name
+            ),
+        );
+        self.commit_fav_changes();
+    }
+    /// Whether the entry named `known` belongs to the session reported as `name`, including
+    /// both names of a pending rename.
+    fn is_entry_of(&self, known: &str, name: &str) -> bool {
+        known == name
+            || self
+                .pending_renames
+                .iter()
+                .any(|(from, to)| known == to && name == from || known == from && name == to)
+    }
+    /// Records that the session was used now, for the `mru` sort.
+    pub fn touch_session(&mut self, name: &str) {
+        if let Some(session) = self.find_session_mut(name) {
+            session.last_used = Some(unix_now());
+        }
+    }
+    pub fn switch_to(&mut self, name: &str) {
+        if self.current_session.as_deref() != Some(name) {
+            if let Some(current) = self.current_session.clone() {
+                self.touch_session(&current);
+            }
+            self.touch_session(name);
+            self.save_quietly();
+        } else if self.ui_changed_at.is_some() {
+            self.save_quietly();
+        }
+        switch_session(Some(name));
+    }
+    /// The most recently used session other than the current one. Derived from the `last_used`
+    /// timestamps rather than remembered, since each session runs its own plugin instance.
+    pub fn previous_session(&self) -> Option<String> {
+        self.fav_sessions
+            .iter()
+            .chain(self.flush_sessions.iter())
+            .filter(|session| Some(&session.name) != self.current_session.as_ref())
+            .filter(|session| session.last_used.is_some())
+            .max_by_key(|session| session.last_used)
+            .map(|session| session.name.clone())
+    }
+    pub fn go_back(&mut self) -> Result<String, String> {
+        let previous = self
+            .previous_session()
+            .ok_or_else(|| "no previous session".to_string())?;
+        self.switch_to(&previous);
+        Ok(previous)
+    }
+    pub fn cycle_favs(&mut self, forward: bool) -> Result<String, String> {
+        if self.fav_sessions.is_empty() {
+            return Err("no favorite sessions".to_string());
+        }
+        let len = self.fav_sessions.len();
+        let current = self
+            .fav_sessions
+            .iter()
+            .position(|s| Some(&s.name) == self.current_session.as_ref());
+        let next = match (current, forward) {
+            (Some(idx), true) => (idx + 1) % len,
+            (Some(idx), false) => (idx + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len - 1,
+        };
+        let name = self.fav_sessions[next].name.clone();
+        self.switch_to(&name);
+        Ok(name)
+    }
+    /// Kills and deletes the given sessions, returning a summary of what was done.
+    pub fn delete_sessions(&mut self, sessions: &[FavSessionInfo]) -> String {
+        let sessions_to_kill: Vec<String> = sessions
+            .iter()
+            .filter(|session| session.is_active)
+            .map(|session| session.name.clone())
+            .collect();
 
-        (
-            self.fav_sessions.iter_mut().collect(),
-            self.flush_sessions.iter_mut().collect(),
-        )
+        kill_sessions(&sessions_to_kill);
+
+        for session in sessions.iter() {
+            delete_dead_session(&session.name);
+        }
+
+        let deleted_names: Vec<&str> = sessions.iter().map(|s| s.name.as_str()).collect();
+        self.fav_sessions
+            .retain(|session| !deleted_names.contains(&session.name.as_str()));
+        self.flush_sessions
+            .retain(|session| !deleted_names.contains(&session.name.as_str()));
+
+        let deleted = sessions.len() - sessions_to_kill.len();
+        match (sessions_to_kill.len(), deleted) {
+            (0, 0) => "nothing to delete".to_string(),
+            (killed, 0) => format!("killed {} sessions", killed),
+            (0, deleted) => format!("deleted {} dead sessions", deleted),
+            (killed, deleted) => {
+                format!("killed {} sessions, deleted {} dead", killed, deleted)
+            }
+        }
     }
-    pub fn get_filtered_sessions(&self) -> (Vec<FavSessionInfo>, Vec<FavSessionInfo>) {
-        let flush_sessions: Vec<FavSessionInfo> = self
-            .flush_sessions
+    /// The marked sessions, or the highlighted one when nothing is marked.
+    pub fn target_sessions(&self) -> Vec<FavSessionInfo> {
+        if self.marked.is_empty() {
+            return self.get_selected_session().into_iter().collect();
+        }
+        self.fav_sessions
             .iter()
-            .filter(|session| {
-                if let Some(filter) = self.filter.clone() {
-                    session.name.to_lowercase().contains(&filter.to_lowercase())
-                } else {
-                    true
-                }
-            })
+            .chain(self.flush_sessions.iter())
+            .filter(|session| self.marked.contains(&session.name))
             .cloned()
-            .collect();
-        let fav_sessions: Vec<FavSessionInfo> = self
+            .collect()
+    }
+    /// Sessions of the active column that pass the filter.
+    pub fn visible_sessions(&self) -> Vec<FavSessionInfo> {
+        let (fav_sessions, flush_sessions) = self.get_filtered_sessions();
+        match self.active_column() {
+            FavMode::NavigateFlush => flush_sessions,
+            _ => fav_sessions,
+        }
+    }
+    /// Remembers both lists so the change about to be made can be undone.
+    pub fn push_undo<S: ToString>(&mut self, description: S) {
+        self.push_undo_step(description.to_string(), None);
+    }
+    /// Records a change undo can only partly revert, explained by `note` when it's undone.
+    pub fn push_undo_with_note<S: ToString>(&mut self, description: S, note: &str) {
+        self.push_undo_step(description.to_string(), Some(note.to_string()));
+    }
+    fn push_undo_step(&mut self, description: String, note: Option<String>) {
+        self.undo_stack.push(UndoStep {
+            description,
+            fav_sessions: self.fav_sessions.clone(),
+            flush_sessions: self.flush_sessions.clone(),
+            note,
+        });
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+    }
+    /// Puts back the lists, numbers, tags and notes from before the last change. Deleted
+    /// sessions can't be brought back, the step's note says so.
+    pub fn undo(&mut self) -> Result<String, String> {
+        let step = self
+            .undo_stack
+            .pop()
+            .ok_or_else(|| "nothing to undo".to_string())?;
+        let current: Vec<FavSessionInfo> = self
             .fav_sessions
             .iter()
-            .filter(|session| {
-                if let Some(filter) = self.filter.clone() {
-                    session.name.to_lowercase().contains(&filter.to_lowercase())
-                } else {
-                    true
-                }
-            })
+            .chain(self.flush_sessions.iter())
             .cloned()
             .collect();
-        (fav_sessions, flush_sessions)
-    }
-
-    pub fn render_navigation(&self, cols: usize, rows: usize) {
-        let half_cols = cols / 2;
-
-        println!(
-            "{} {}",
-            ">".cyan().bold(),
-            if let Some(filter) = &self.filter {
-                filter.dimmed().italic().to_string()
+        let mut fav_sessions = restore(&step.fav_sessions, &current);
+        let mut flush_sessions = restore(&step.flush_sessions, &current);
+        for session in current {
+            let restored = fav_sessions
+                .iter()
+                .chain(flush_sessions.iter())
+                .any(|s| s.name == session.name);
+            if restored {
+                continue;
+            }
+            if self.fav_sessions.iter().any(|s| s.name == session.name) {
+                fav_sessions.push(session);
             } else {
-                "(filter)".dimmed().italic().to_string()
-            },
+                flush_sessions.push(session);
+            }
+        }
+        self.fav_sessions = fav_sessions;
+        self.flush_sessions = flush_sessions;
+        self.commit_fav_changes();
+        Ok(match step.note {
+            Some(note) => format!("undid {}, {}", step.description, note),
+            None => format!("undid {}", step.description),
+        })
+    }
+    /// Writes the given sessions as JSON to `path`.
+    pub fn export_sessions(&self, sessions: &[FavSessionInfo], path: &str) {
+        let json = serde_json::to_string(sessions).unwrap();
+        let mut data = BTreeMap::new();
+        data.insert(
+            FavsCommandType::get_command_key(),
+            FavsCommandType::Export.to_string(),
+        );
+        data.insert(
+            "message".to_string(),
+            format!("exported {} sessions to {}", sessions.len(), path),
         );
 
-        let favs_title = if self.mode == FavMode::NavigateFavs {
-            format!("{}", "Favorites".bold().blue())
-        } else {
-            format!("{}", "Favorites".bold().dimmed())
+        run_command(
+            &[
+                "bash",
+                "-c",
+                format!("echo {} > {}", shell_quote(&json), shell_path(path)).as_str(),
+            ],
+            data,
+        );
+    }
+    pub fn get_query(&self) -> Result<Option<Query>, String> {
+        match &self.filter {
+            Some(filter) => Query::parse(&filter.value),
+            None => Ok(None),
+        }
+    }
+    /// Presets from the plugin configuration followed by the ones saved in the cache.
+    pub fn get_presets(&self) -> Vec<(String, String)> {
+        self.config_presets
+            .iter()
+            .filter(|(name, _)| !self.presets.contains_key(*name))
+            .chain(self.presets.iter())
+            .map(|(name, query)| (name.clone(), query.clone()))
+            .collect()
+    }
+    pub fn push_filter_history(&mut self) {
+        self.history_index = None;
+        let Some(filter) = &self.filter else {
+            return;
         };
-
-        print_text_with_coordinates(Text::new(favs_title), 0, 1, None, None);
-
-        let sessions_space = rows.saturating_sub(3);
-        let skip = if self.cursor > sessions_space.saturating_sub(1) {
-            self.cursor.saturating_sub(sessions_space.saturating_sub(1))
-        } else {
-            0
+        let value = filter.value.trim().to_string();
+        if value.is_empty() {
+            return;
+        }
+        self.filter_history.retain(|query| query != &value);
+        self.filter_history.push(value);
+    }
+    pub fn recall_filter_history(&mut self, older: bool) {
+        if self.filter_history.is_empty() {
+            return;
+        }
+        let last = self.filter_history.len() - 1;
+        self.history_index = match (self.history_index, older) {
+            (None, true) => Some(last),
+            (None, false) => return,
+            (Some(idx), true) => Some(idx.saturating_sub(1)),
+            (Some(idx), false) if idx < last => Some(idx + 1),
+            (Some(_), false) => None,
         };
-
-        for (i, session) in self
-            .fav_sessions
+        let value = match self.history_index {
+            Some(idx) => self.filter_history[idx].as_str(),
+            None => "",
+        };
+        self.filter = Some(TextInput::new(value));
+    }
+    /// Sessions that pass the filter, best matches first and ties in the sort order.
+    fn filter_sessions(
+        &self,
+        sessions: &[FavSessionInfo],
+        query: Option<&Query>,
+    ) -> Vec<FavSessionInfo> {
+        let mut sessions = sessions.to_vec();
+        self.sort_mode.sort(&mut sessions);
+        let Some(query) = query else {
+            return sessions;
+        };
+        let mut matches: Vec<(i64, &FavSessionInfo)> = sessions
             .iter()
-            .filter(|session| {
-                if let Some(filter) = self.filter.clone() {
-                    session.name.to_lowercase().contains(&filter.to_lowercase())
-                } else {
-                    true
-                }
+            .filter_map(|session| {
+                query
+                    .score(session, self.search_contents)
+                    .map(|score| (score, session))
             })
-            .skip({
-                if self.mode == FavMode::NavigateFavs {
-                    skip
-                } else {
-                    0
-                }
-            })
-            .enumerate()
-        {
-            if i >= sessions_space {
-                break;
-            }
-            let selected_idx = self.cursor.min(sessions_space - 1);
-            let assigned_number = if let Some(assigned) = session.assigned_number {
-                format!(" ({})", assigned).dimmed().to_string()
-            } else {
-                "".to_string()
-            };
+            .collect();
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches
+            .into_iter()
+            .map(|(_, session)| session.clone())
+            .collect()
+    }
+    pub fn get_filtered_sessions(&self) -> (Vec<FavSessionInfo>, Vec<FavSessionInfo>) {
+        self.sessions_matching(self.get_query().ok().flatten().as_ref())
+    }
+    fn sessions_matching(
+        &self,
+        query: Option<&Query>,
+    ) -> (Vec<FavSessionInfo>, Vec<FavSessionInfo>) {
+        (
+            self.filter_sessions(&self.fav_sessions, query),
+            self.filter_sessions(&self.flush_sessions, query),
+        )
+    }
+    fn session_line(
+        &self,
+        session: &FavSessionInfo,
+        query: Option<&Query>,
+        selected: bool,
+        assigning: bool,
+        width: usize,
+    ) -> Line {
+        let mut line = jump_label(self, &session.name).unwrap_or_default();
+        if self.marked.contains(&session.name) {
+            line = line.styled("+ ", Role::Prompt);
+        }
+        if selected || assigning {
+            line = line.plain("> ");
+        }
 
-            let counters = if self.display_tab_panes {
-                if session.is_active {
-                    format!(" ({} tabs, {} panes)", session.tabs, session.panes)
-                } else {
-                    " (resurrect)".to_string()
-                }
+        let indices = query
+            .map(|query| query.highlight(&session.name))
+            .unwrap_or_default();
+        for (i, c) in session.name.chars().enumerate() {
+            line = if indices.contains(&i) {
+                line.styled(c, Role::Match)
             } else {
-                "".to_string()
+                line.plain(c)
             };
+        }
 
-            let text = if self.mode == FavMode::NavigateFavs && selected_idx == i {
-                let selected = format!(
-                    "> {}{}{}",
-                    session.name.clone().underline(),
-                    assigned_number,
-                    counters.dimmed()
-                );
-                Text::new(selected)
-            } else if self.mode == FavMode::AssignNumber
-                && self.current_column == Some(FavMode::NavigateFavs)
-                && selected_idx == i
-            {
-                let editing_access_text =
-                    format!("> {} {}", session.name.clone(), "(0-9)".dimmed());
-                Text::new(editing_access_text)
+        if assigning && self.hotkey_input.is_empty() {
+            return line.plain(" (0-9 with Alt/Ctrl, or keys then Enter)");
+        }
+        if assigning {
+            let keys: Vec<String> = self.hotkey_input.iter().map(|c| c.to_string()).collect();
+            return line
+                .plain(" hotkey: ")
+                .styled(keys.join(" "), Role::Number)
+                .styled('_', Role::Prompt);
+        }
+        if let Some(assigned) = session.assigned_number {
+            line = line.styled(format!(" ({})", number_label(assigned)), Role::Number);
+        }
+        if let Some(hotkey) = &session.hotkey {
+            line = line.styled(format!(" [{}]", hotkey), Role::Number);
+        }
+        if self.display_tab_panes {
+            line = if session.is_active {
+                line.plain(format!(" ({} tabs, {} panes)", session.tabs, session.panes))
             } else {
-                Text::new(format!(
-                    "{}{}{}",
-                    session.name.clone(),
-                    assigned_number,
-                    counters.dimmed()
-                ))
+                line.plain(" (resurrect)")
             };
-
-            print_text_with_coordinates(text, 0, 2 + i, None, None);
         }
+        if let (true, Some(context)) = (
+            self.search_contents,
+            query.and_then(|query| query.content_match(session)),
+        ) {
+            // The match is why the session is listed, so shorten the rest of the line first,
+            // down to half the width.
+            let context = Line::new().plain(format!(" ↳ {}", context));
+            let line_width = width
+                .saturating_sub(context.len())
+                .max(line.len().min(width / 2));
+            line = line
+                .truncate(line_width)
+                .append(context.truncate(width.saturating_sub(line_width)));
+        }
+        if selected {
+            line = line.selected();
+        }
+        line
+    }
+    fn render_column(
+        &self,
+        sessions: &[FavSessionInfo],
+        query: Option<&Query>,
+        column: FavMode,
+        rect: Rect,
+    ) {
+        if rect.height == 0 || rect.width == 0 {
+            return;
+        }
+        let sessions_space = sessions_space(&rect);
+        let cursor = self.column_cursor(&column);
+        let above = cursor.offset;
+        let below = sessions
+            .len()
+            .saturating_sub(cursor.offset + sessions_space);
 
-        let flush_title = if self.mode == FavMode::NavigateFlush {
-            format!("{}", "Flush".bold().blue())
+        let title = if column == FavMode::NavigateFavs {
+            "Favorites"
         } else {
-            format!("{}", "Flush".bold().dimmed())
+            "Flush"
         };
-        print_text_with_coordinates(Text::new(flush_title), half_cols, 1, None, None);
+        let mut title = if self.mode == column {
+            Line::new().styled(title, Role::Title)
+        } else {
+            Line::new().plain(title)
+        };
+        let marked = sessions
+            .iter()
+            .filter(|session| self.marked.contains(&session.name))
+            .count();
+        if marked > 0 {
+            title = title.styled(format!(" ({} marked)", marked), Role::Prompt);
+        }
+        if above > 0 {
+            title = title.plain(format!(" ↑ {} more", above));
+        }
+        if below > 0 {
+            title = title.plain(format!(" ↓ {} more", below));
+        }
+        self.theme.print(&title, rect.x, rect.y, Some(rect.width));
 
-        for (i, session) in self
-            .flush_sessions
+        // The palette acts on the highlighted session, so keep showing it while typing.
+        let focused =
+            self.mode == column || self.mode == FavMode::Command && self.active_column() == column;
+        let assigning =
+            self.mode == FavMode::AssignNumber && self.current_column.as_ref() == Some(&column);
+
+        for (i, session) in sessions
             .iter()
-            .filter(|session| {
-                if let Some(filter) = self.filter.clone() {
-                    session.name.to_lowercase().contains(&filter.to_lowercase())
-                } else {
-                    true
-                }
-            })
-            .skip({
-                if self.mode == FavMode::NavigateFlush {
-                    skip
-                } else {
-                    0
-                }
-            })
             .enumerate()
+            .skip(cursor.offset)
+            .take(sessions_space)
         {
-            if i >= sessions_space {
-                break;
-            }
-
-            let selected_idx = self.cursor.min(sessions_space - 1);
-            let assigned_number = if let Some(assigned) = session.assigned_number {
-                format!(" ({})", assigned).dimmed().to_string()
-            } else {
-                "".to_string()
-            };
+            let width = rect.width.saturating_sub(1);
+            let line = self.session_line(
+                session,
+                query,
+                focused && cursor.index == i,
+                assigning && cursor.index == i,
+                width,
+            );
+            self.theme
+                .print(&line, rect.x, rect.y + 1 + i - cursor.offset, Some(width));
+        }
+    }
+    fn filter_line(&self) -> Line {
+        let mut line = Line::new().styled("> ", Role::Prompt);
 
-            let counts = if self.display_tab_panes {
-                format!(" ({} tabs, {} panes)", session.tabs, session.panes)
-            } else {
-                "".to_string()
-            };
+        match &self.filter {
+            Some(filter) if self.mode == FavMode::Filter => {
+                let (before, current, after) = filter.split_at_cursor();
+                line = line
+                    .plain(before)
+                    .styled(current.unwrap_or('_'), Role::Prompt)
+                    .plain(after);
+            }
+            Some(filter) => line = line.plain(&filter.value),
+            None => line = line.plain("(filter)"),
+        }
 
-            let text = if self.mode == FavMode::NavigateFlush && selected_idx == i {
-                let selected = format!(
-                    "> {}{}{}",
-                    session.name.clone().underline(),
-                    assigned_number,
-                    counts.dimmed()
-                );
-                Text::new(selected)
-            } else if self.mode == FavMode::AssignNumber
-                && self.current_column == Some(FavMode::NavigateFlush)
-                && selected_idx == i
-            {
-                let editing_access_text = format!("> {} ({})", session.name.clone(), "0-9");
-                Text::new(editing_access_text)
-            } else {
-                Text::new(format!(
-                    "{}{}{}",
-                    session.name.clone(),
-                    assigned_number,
-                    counts.dimmed()
-                ))
-            };
-            print_text_with_coordinates(text, half_cols, 2 + i, None, None);
+        let preset = self.filter.as_ref().and_then(|filter| {
+            self.get_presets()
+                .into_iter()
+                .find(|(_, query)| query == &filter.value)
+        });
+        if let Some((name, _)) = preset {
+            line = line.styled(format!("  [{}]", name), Role::Section);
+        }
+        if self.search_contents {
+            line = line.plain("  [+tabs/panes]");
         }
+        if self.sort_mode != SortMode::Manual {
+            line = line.plain(format!("  [sort: {}]", self.sort_mode));
+        }
+        if let Err(e) = self.get_query() {
+            line = line.styled(format!("  {}", e), Role::Error);
+        }
+        line
+    }
 
-        if self.mode == FavMode::Filter {
+    pub fn render_navigation(&mut self, cols: usize, rows: usize) {
+        if rows == 0 || cols == 0 {
             return;
         }
+        self.scroll_columns();
+        let layout = self.get_layout();
 
-        let help_text = format!("{}", "Press '?' for help".dimmed().italic());
-        print_text_with_coordinates(Text::new(help_text), 0, rows - 1, None, None);
-    }
-    pub fn render_help_commands(&self) {
-        let modes = FavMode::variants();
-        for mode in modes.iter() {
-            if mode == &FavMode::NavigateFlush {
-                continue;
-            }
-            println!("{}", mode.clone().dimmed().italic().red());
-            let commands = mode.clone().get_commands();
+        let header = match self.mode {
+            FavMode::Command => palette_line(self),
+            _ => self.filter_line(),
+        };
+        self.theme.print(&header, 0, 0, Some(cols));
 
-            for command in commands.iter() {
-                println!("  {} - {}", command.0.purple(), command.1);
-            }
+        // Parsed once for the whole render, every session line highlights its matches.
+        let query = self.get_query().ok().flatten();
+        let (fav_sessions, flush_sessions) = self.sessions_matching(query.as_ref());
+        self.render_column(
+            &fav_sessions,
+            query.as_ref(),
+            FavMode::NavigateFavs,
+            layout.favs,
+        );
+        self.render_column(
+            &flush_sessions,
+            query.as_ref(),
+            FavMode::NavigateFlush,
+            layout.flush,
+        );
+
+        if let Some(rect) = layout.details {
+            render_details(self, rect);
+        }
+
+        if let Some(footer) = layout.footer {
+            let prompt = match (&self.tag_input, &self.rename_input) {
+                (Some(tag_input), _) => Some(("Tag #", tag_input)),
+                (None, Some(rename_input)) => Some(("Rename to ", rename_input)),
+                (None, None) => None,
+            };
+            let question = self
+                .rename_questions
+                .first()
+                .filter(|_| self.mode == FavMode::Renames);
+            let navigating = matches!(self.mode, FavMode::NavigateFavs | FavMode::NavigateFlush);
+            let footer_line = match (prompt, question, &self.status) {
+                (Some((label, input)), _, _) => {
+                    let (before, current, after) = input.split_at_cursor();
+                    Line::new()
+                        .styled(label, Role::Prompt)
+                        .plain(before)
+                        .styled(current.unwrap_or('_'), Role::Prompt)
+                        .plain(after)
+                }
+                (None, Some(guess), _) => Line::new()
+                    .styled(
+                        format!("Was {} renamed to {}?", guess.previous.name, guess.new_name),
+                        Role::Warning,
+                    )
+                    .plain(format!(
+                        " {} yes  {} no  {} later",
+                        self.keymap.label(&Action::Accept),
+                        self.keymap.label(&Action::Reject),
+                        self.keymap.label(&Action::Cancel),
+                    )),
+                (None, None, _) if self.mode == FavMode::Command => palette_footer(self),
+                (None, None, _) if self.mode == FavMode::Jump => Line::new()
+                    .styled("Jump to ", Role::Prompt)
+                    .plain(&self.jump_input)
+                    .styled('_', Role::Prompt),
+                (None, None, Some(status)) => status.line(),
+                (None, None, None) if navigating && !self.rename_questions.is_empty() => {
+                    Line::new()
+                        .styled(
+                            format!("{} possible renames", self.rename_questions.len()),
+                            Role::Warning,
+                        )
+                        .plain(format!(
+                            ", press {} to review",
+                            self.keymap.label(&Action::Renames)
+                        ))
+                }
+                (None, None, None) if self.mode == FavMode::Filter => return,
+                (None, None, None) => Line::new().plain("Press '?' for help"),
+            };
+            self.theme.print(&footer_line, 0, footer, Some(cols));
         }
     }
     pub fn load_cache(&self) {
@@ -323,10 +1167,95 @@ impl Favs {
         );
 
         run_command(
-            &["bash", "-c", format!("cat {}", self.cache_dir).as_str()],
+            &[
+                "bash",
+                "-c",
+                format!("cat {}", shell_path(&self.cache_dir)).as_str(),
+            ],
+            data,
+        );
+    }
+    fn load_backup(&self) {
+        let mut data = BTreeMap::new();
+        data.insert(
+            FavsCommandType::get_command_key(),
+            FavsCommandType::ReadBackup.to_string(),
+        );
+
+        run_command(
+            &[
+                "bash",
+                "-c",
+                format!("cat {}.bak", shell_path(&self.cache_dir)).as_str(),
+            ],
             data,
         );
     }
+    fn apply_cache(&mut self, cache: FavsJson) {
+        self.fav_sessions = cache.favs;
+        self.flush_sessions = cache.flush;
+        self.presets = cache.presets;
+        self.ui_states = cache.ui_state;
+        if self.restore_ui && self.display == FavsDisplay::Manager {
+            if let Some(state) = self.ui_states.get(&self.ui_state_key()).cloned() {
+                state.restore(self);
+            }
+        }
+    }
+    /// Takes the lists and metadata from a cache saved by another instance, keeping the live
+    /// state of the sessions already known. Used by the bar, and before deferred work so
+    /// switches pick their target from, and saves keep, what other instances saved.
+    fn refresh_from_cache(&mut self, cache: FavsJson) {
+        let mut live: Vec<FavSessionInfo> = std::mem::take(&mut self.fav_sessions);
+        live.append(&mut self.flush_sessions);
+        let mut merge =
+            |cached: FavSessionInfo| match live.iter().position(|s| s.name == cached.name) {
+                Some(index) => live.remove(index).with_metadata(&cached),
+                None => cached,
+            };
+        self.fav_sessions = cache.favs.into_iter().map(&mut merge).collect();
+        self.flush_sessions = cache.flush.into_iter().map(&mut merge).collect();
+        self.flush_sessions.append(&mut live);
+        self.presets = cache.presets;
+        self.ui_states = cache.ui_state;
+    }
+    /// The session the UI state is saved under, all of them share it unless
+    /// `ui_state_per_session` is set.
+    fn ui_state_key(&self) -> String {
+        match &self.current_session {
+            Some(session) if self.ui_state_per_session => session.clone(),
+            _ => String::new(),
+        }
+    }
+    fn ui_states_to_save(&self) -> BTreeMap<String, UiState> {
+        let mut ui_states = self.ui_states.clone();
+        // Instances started by `zellij pipe` never render and have no UI state of their own.
+        let shown = self.rows > 0;
+        if self.restore_ui && self.has_loaded && shown && self.display == FavsDisplay::Manager {
+            ui_states.insert(self.ui_state_key(), UiState::capture(self));
+        }
+        ui_states
+    }
+    /// Schedules saving the UI state if it differs from `before`.
+    fn track_ui_change(&mut self, before: UiState) {
+        if self.restore_ui && self.has_loaded && UiState::capture(self) != before {
+            self.ui_changed_at = Some(Instant::now());
+            set_timeout(UI_SAVE_DELAY.as_secs_f64());
+        }
+    }
+    fn finish_loading(&mut self) {
+        self.has_loaded = true;
+        run_deferred(self);
+    }
+    pub fn read_no_color(&self) {
+        let mut data = BTreeMap::new();
+        data.insert(
+            FavsCommandType::get_command_key(),
+            FavsCommandType::ReadNoColor.to_string(),
+        );
+
+        run_command(&["bash", "-c", "printenv NO_COLOR"], data);
+    }
 }
 
 impl ZellijPlugin for Favs {
@@ -334,19 +1263,68 @@ impl ZellijPlugin for Favs {
         if let Some(cache_dir) = configuration.get("cache_dir") {
             self.cache_dir = cache_dir.to_string();
         }
+        if let Some(export_path) = configuration.get("export_path") {
+            self.export_path = export_path.to_string();
+        }
         if let Some(display_tab_panes) = configuration.get("display_tab_panes") {
             self.display_tab_panes = matches!(display_tab_panes.trim(), "true" | "t" | "y" | "1");
         }
+        if let Some(search_contents) = configuration.get("search_contents") {
+            self.search_contents = matches!(search_contents.trim(), "true" | "t" | "y" | "1");
+        }
+        for (key, query) in configuration.iter() {
+            if let Some(name) = key.strip_prefix("preset_") {
+                self.config_presets
+                    .insert(name.to_string(), query.to_string());
+            }
+        }
+        if let Some(sort_mode) = configuration.get("sort") {
+            match SortMode::parse(sort_mode) {
+                Some(sort_mode) => {
+                    self.sort_mode = sort_mode;
+                    self.sort_configured = true;
+                }
+                None => self.notify(
+                    Severity::Warning,
+                    format!("sort: unknown sort mode '{}'", sort_mode),
+                ),
+            }
+        }
+        if let Some(show_details) = configuration.get("details") {
+            self.show_details = matches!(show_details.trim(), "true" | "t" | "y" | "1");
+        }
+        if let Some(start_fresh) = configuration.get("start_fresh") {
+            self.restore_ui = !matches!(start_fresh.trim(), "true" | "t" | "y" | "1");
+        }
+        if let Some(per_session) = configuration.get("ui_state_per_session") {
+            self.ui_state_per_session = matches!(per_session.trim(), "true" | "t" | "y" | "1");
+        }
+        let (theme, mut errors) = Theme::from_config(&configuration);
+        self.theme = theme;
+        let (keymap, keymap_errors) = Keymap::from_config(&configuration);
+        self.keymap = keymap;
+        errors.extend(keymap_errors);
+        if !errors.is_empty() {
+            self.notify(Severity::Warning, errors.join(", "));
+        }
+        if let Some(display) = configuration.get("mode") {
+            self.display = display.into();
+        }
 
         request_permission(&[
             PermissionType::ReadApplicationState,
             PermissionType::ChangeApplicationState,
             PermissionType::RunCommands,
+            PermissionType::ReadCliPipes,
         ]);
         subscribe(&[
             EventType::Key,
             EventType::SessionUpdate,
             EventType::RunCommandResult,
+            EventType::Mouse,
+            EventType::PastedText,
+            EventType::Timer,
+            EventType::PermissionRequestResult,
         ]);
     }
 
@@ -354,105 +1332,205 @@ impl ZellijPlugin for Favs {
         let mut render = false;
         match event {
             Event::Key(key) => {
-                render = self.match_key(&key.bare_key);
+                let before = UiState::capture(self);
+                render = self.match_key(&key);
+                self.track_ui_change(before);
+            }
+            Event::PastedText(text) => {
+                render = match_filter_paste(self, &text) || match_palette_paste(self, &text);
+            }
+            Event::Mouse(mouse) => {
+                let before = UiState::capture(self);
+                render = self.match_mouse(&mouse);
+                self.track_ui_change(before);
+            }
+            Event::PermissionRequestResult(status) => {
+                if status == PermissionStatus::Denied {
+                    self.notify(Severity::Error, "permission denied");
+                    render = true;
+                }
+                if self.display == FavsDisplay::Bar {
+                    set_selectable(false);
+                }
+            }
+            Event::Timer(_) => {
+                if self
+                    .ui_changed_at
+                    .is_some_and(|changed| changed.elapsed() >= UI_SAVE_DELAY)
+                {
+                    self.ui_changed_at = None;
+                    defer(self, Deferred::SaveUi);
+                }
+                render = self.expire_status();
             }
             Event::SessionUpdate(sessions_info, resurrectable_session_list) => {
                 if !self.has_loaded {
                     self.load_cache();
+                    self.read_no_color();
                 }
-                let mut all_sessions: Vec<(&String, usize, usize, bool)> = sessions_info
+                let current_session = sessions_info
                     .iter()
-                    .map(|s| {
-                        (
-                            &s.name,
-                            s.tabs.len(),
-                            s.panes
-                                .panes
-                                .values()
-                                .flat_map(|v| v.iter())
-                                .filter(|pane| !(pane.is_plugin))
-                                .count(),
-                            true,
-                        )
-                    })
-                    .collect();
-                all_sessions.extend(
-                    resurrectable_session_list
-                        .iter()
-                        .map(|s| (&s.0, 0, 0, false)),
-                );
-
-                let mut fav_sessions: Vec<FavSessionInfo> = vec![];
-                let mut flush_sessions: Vec<FavSessionInfo> = vec![];
-
-                for (session_name, tabs, panes, is_active) in all_sessions {
-                    if let Some(fav_session) =
-                        self.fav_sessions.iter().find(|s| &s.name == session_name)
-                    {
-                        fav_sessions.push(FavSessionInfo {
-                            tabs,
-                            panes,
-                            is_active,
-                            ..fav_session.clone()
-                        });
-                    } else if let Some(flush_session) =
-                        self.flush_sessions.iter().find(|s| &s.name == session_name)
-                    {
-                        flush_sessions.push(FavSessionInfo {
-                            tabs,
-                            panes,
-                            is_active,
-                            ..flush_session.clone()
-                        });
-                    } else {
-                        flush_sessions.push(FavSessionInfo {
-                            name: session_name.to_string(),
-                            is_active,
-                            assigned_number: None,
-                            tabs,
-                            panes,
-                        });
+                    .find(|s| s.is_current_session)
+                    .map(|s| s.name.clone());
+                if self.current_session != current_session {
+                    if let Some(current) = current_session.as_deref() {
+                        self.touch_session(current);
                     }
+                    self.current_session = current_session;
+                    render = true;
                 }
+                self.resurrectable_ages = resurrectable_session_list.iter().cloned().collect();
+                let mut all_sessions: Vec<FavSessionInfo> =
+                    sessions_info.iter().map(FavSessionInfo::from).collect();
+                all_sessions.extend(resurrectable_session_list.iter().map(|s| FavSessionInfo {
+                    name: s.0.clone(),
+                    ..Default::default()
+                }));
 
-                if self.fav_sessions != fav_sessions
-                    || self.flush_sessions != flush_sessions && self.has_loaded
-                {
+                let reported: BTreeSet<String> =
+                    all_sessions.iter().map(|s| s.name.clone()).collect();
+                let gone = self.gone_sessions(&reported);
+                // Keep the lists in their stored order, Zellij reports sessions in its own.
+                let mut take_live = |stored: &[FavSessionInfo]| -> Vec<FavSessionInfo> {
+                    stored
+                        .iter()
+                        .filter_map(|stored| {
+                            let index = all_sessions
+                                .iter()
+                                .position(|s| self.is_entry_of(&stored.name, &s.name))?;
+                            Some(all_sessions.remove(index).with_metadata(stored))
+                        })
+                        .collect()
+                };
+                let fav_sessions = take_live(&self.fav_sessions);
+                let mut flush_sessions = take_live(&self.flush_sessions);
+                let appeared: Vec<FavSessionInfo> = all_sessions;
+                flush_sessions.extend(appeared.iter().cloned());
+
+                self.pending_renames
+                    .retain(|_, new_name| !reported.contains(new_name));
+                self.rename_questions
+                    .retain(|question| reported.contains(&question.new_name));
+                if self.rename_questions.is_empty() {
+                    close_rename_questions(self);
+                }
+
+                let same_saved_state = |old: &[FavSessionInfo], new: &[FavSessionInfo]| {
+                    old.len() == new.len()
+                        && old
+                            .iter()
+                            .zip(new.iter())
+                            .all(|(old, new)| old.saved_state() == new.saved_state())
+                };
+                let saved_state_changed = !same_saved_state(&self.fav_sessions, &fav_sessions)
+                    || !same_saved_state(&self.flush_sessions, &flush_sessions);
+                let live_state_changed =
+                    self.fav_sessions != fav_sessions || self.flush_sessions != flush_sessions;
+
+                if self.has_loaded && !saved_state_changed && live_state_changed {
+                    // Only live tabs and panes changed, they're saved with the next change.
                     self.fav_sessions = fav_sessions;
                     self.flush_sessions = flush_sessions;
-
-                    match self.mode {
-                        FavMode::NavigateFavs => {
-                            self.cursor = self.cursor.min(self.fav_sessions.len());
-                        }
-                        FavMode::NavigateFlush => {
-                            self.cursor = self.cursor.min(self.flush_sessions.len());
-                        }
-                        _ => {}
+                    render = true;
+                } else if self.has_loaded && saved_state_changed {
+                    self.fav_sessions = fav_sessions;
+                    self.flush_sessions = flush_sessions;
+                    // Sessions renamed outside the plugin show up as one session leaving and
+                    // another one arriving, so match them up to keep their metadata.
+                    let (renamed, ambiguous) = detect_renames(&gone, &appeared);
+                    for guess in renamed.iter() {
+                        self.apply_rename(guess);
+                        self.notify(
+                            Severity::Info,
+                            format!("{} was renamed to {}", guess.previous.name, guess.new_name),
+                        );
                     }
-                    self.commit_fav_changes();
+                    self.rename_questions.extend(ambiguous);
+                    let marked = std::mem::take(&mut self.marked);
+                    self.marked = marked
+                        .into_iter()
+                        .filter(|name| self.find_session(name).is_some())
+                        .collect();
+
+                    self.save_quietly();
                     render = true;
                 }
+                // Favorites, numbers and hotkeys are changed in the manager, pick them up.
+                if self.display == FavsDisplay::Bar && self.has_loaded {
+                    self.load_cache();
+                }
             }
-            Event::RunCommandResult(exit_code, stdout, _stderr, context) => {
-                if exit_code.is_some() && exit_code != Some(0) {
-                    self.has_loaded = true;
+            Event::RunCommandResult(exit_code, stdout, stderr, context) => {
+                let command_type: Option<FavsCommandType> = context
+                    .get(FavsCommandType::get_command_key().as_str())
+                    .map(|command_type| command_type.into());
+                if let Some(FavsCommandType::ReadNoColor) = command_type {
+                    let no_color = String::from_utf8_lossy(&stdout);
+                    self.theme.monochrome |= exit_code == Some(0) && !no_color.trim().is_empty();
                     return true;
                 }
-                if let Some(command_type) = context.get(FavsCommandType::get_command_key().as_str())
-                {
-                    let command_type_enum: FavsCommandType = command_type.into();
-                    if let FavsCommandType::ReadCache = command_type_enum {
-                        if let Ok(json_string) = String::from_utf8(stdout) {
-                            if let Ok(sessions) = serde_json::from_str::<FavsJson>(&json_string) {
-                                self.fav_sessions = sessions.favs;
-                                self.flush_sessions = sessions.flush;
+                let failed = exit_code.is_some() && exit_code != Some(0);
+                let stderr = String::from_utf8_lossy(&stderr);
+                let stdout = String::from_utf8_lossy(&stdout);
+                match command_type {
+                    Some(FavsCommandType::ReadCache) if failed => {
+                        // A missing cache is expected the first time the plugin runs.
+                        if !stderr.contains("No such file") {
+                            self.notify(
+                                Severity::Error,
+                                format!("failed to read cache: {}", stderr.trim()),
+                            );
+                        }
+                        self.finish_loading();
+                    }
+                    Some(FavsCommandType::ReadCache) => {
+                        match serde_json::from_str::<FavsJson>(&stdout) {
+                            Ok(cache) if self.has_loaded => {
+                                self.refresh_from_cache(cache);
+                                run_deferred(self);
+                            }
+                            Ok(cache) => {
+                                self.apply_cache(cache);
+                                self.finish_loading();
+                            }
+                            // Keep the lists as they are until the manager saves a readable cache.
+                            Err(_) if self.has_loaded => run_deferred(self),
+                            Err(_) => self.load_backup(),
+                        }
+                    }
+                    Some(FavsCommandType::ReadBackup) => {
+                        match serde_json::from_str::<FavsJson>(&stdout) {
+                            Ok(cache) if !failed => {
+                                self.apply_cache(cache);
+                                self.notify(Severity::Warning, "cache corrupt, loaded backup");
                             }
+                            _ => self.notify(Severity::Error, "cache corrupt, no usable backup"),
+                        }
+                        self.finish_loading();
+                    }
+                    Some(FavsCommandType::WriteCache) if failed => {
+                        self.notify(
+                            Severity::Error,
+                            format!("failed to save: {}", stderr.trim()),
+                        );
+                    }
+                    Some(FavsCommandType::WriteCache) if !context.contains_key("quiet") => {
+                        self.notify(Severity::Info, "saved");
+                    }
+                    Some(FavsCommandType::Export) if failed => {
+                        self.notify(
+                            Severity::Error,
+                            format!("failed to export: {}", stderr.trim()),
+                        );
+                    }
+                    Some(FavsCommandType::Export) => {
+                        if let Some(message) = context.get("message") {
+                            self.notify(Severity::Success, message);
                         }
-                        self.has_loaded = true;
-                        render = true;
                     }
+                    _ => {}
                 }
+                render = true;
             }
             _ => {}
         }
@@ -460,11 +1538,20 @@ impl ZellijPlugin for Favs {
         render
     }
 
+    fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
+        match_pipe_message(self, pipe_message)
+    }
+
     fn render(&mut self, rows: usize, cols: usize) {
+        self.rows = rows;
+        self.cols = cols;
+        if self.display == FavsDisplay::Bar {
+            render_bar(self, cols);
+            return;
+        }
         match self.mode {
-            FavMode::Help => {
-                self.render_help_commands();
-            }
+            FavMode::Help => render_help(self, rows, cols),
+            FavMode::Presets => render_presets(self, rows),
             _ => self.render_navigation(cols, rows),
         }
     }
@@ -2,46 +2,68 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Serialize, Deserialize, PartialEq)]
+use crate::keymap::{Action, Keymap};
+
+#[derive(Default, Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub enum FavMode {
     #[default]
     NavigateFavs,
     NavigateFlush,
     AssignNumber,
     Filter,
+    Presets,
     Help,
+    Command,
+    Jump,
+    /// Answering whether sessions that disappeared were renamed to ones that appeared.
+    Renames,
 }
 
 impl FavMode {
-    pub fn get_commands(self) -> Vec<(&'static str, &'static str)> {
-        match self {
-            FavMode::Filter => {
-                vec![
-                    ("<Enter> | <Tab>", "Use filter"),
-                    ("<Esc>", "Close filter"),
-                    ("<Backspace>", "Delete last character"),
-                    ("<Left>", "Go to Favs"),
-                    ("<Right>", "Go to Flush"),
-                    ("<Char>", "Type character to filter"),
-                ]
-            }
-            FavMode::NavigateFavs | FavMode::NavigateFlush => vec![
-                ("<Enter>", "Open session"),
-                ("<Space>", "Move session to Flush/Favorites"),
-                ("<Tab>", "Navigate Flush/Favorites items"),
-                ("a", "Add quick access number"),
-                ("t", "Toggle tabs & panes counter"),
-                ("↑k | ↓j", "Move cursor"),
-                ("/", "Filter"),
-                ("f", "Delete Flush Sessions"),
-                ("?", "Help"),
-                ("<Esc> | 'q'", "Close"),
+    /// Bindings of the mode as (keys, description), generated from the active keymap.
+    pub fn get_commands(self, keymap: &Keymap) -> Vec<(String, String)> {
+        let mut commands: Vec<(String, String)> = Action::for_mode(&self)
+            .iter()
+            .map(|action| (keymap.label(action), action.description(&self).to_string()))
+            .filter(|(keys, _)| !keys.is_empty())
+            .collect();
+        let extra: &[(&str, &str)] = match self {
+            FavMode::NavigateFavs | FavMode::NavigateFlush => &[
+                ("0 - 9", "Open session with quick access number"),
+                ("Alt/Ctrl/Ctrl-Alt 0 - 9", "Open session from another bank"),
+                ("<Hotkey>", "Open session with its hotkey, e.g. g a"),
             ],
-            FavMode::Help => vec![("<Esc> | 'q'", "Close help")],
-            FavMode::AssignNumber => {
-                vec![("0 - 9", "Assign quick access number"), ("<Esc>", "Close")]
-            }
-        }
+            FavMode::Filter => &[
+                ("<Char>", "Type character to filter"),
+                (
+                    "active: dead: num:N tabs>N #tag",
+                    "Qualifiers, negate with -, combine with | or",
+                ),
+            ],
+            FavMode::AssignNumber => &[
+                ("0 - 9", "Assign quick access number"),
+                ("Alt/Ctrl/Ctrl-Alt 0 - 9", "Assign number from another bank"),
+                ("<Char>", "Type hotkey, one key at a time"),
+            ],
+            FavMode::Command => &[
+                (
+                    "<Char>",
+                    "Type command, e.g. tag foo, sort mru or flush --dry-run",
+                ),
+                (
+                    "[session]",
+                    "Defaults to the marked or highlighted sessions",
+                ),
+            ],
+            FavMode::Jump => &[("<Label>", "Open the session with that label")],
+            FavMode::Presets | FavMode::Help | FavMode::Renames => &[],
+        };
+        commands.extend(
+            extra
+                .iter()
+                .map(|(keys, description)| (keys.to_string(), description.to_string())),
+        );
+        commands
     }
     pub fn variants() -> Vec<Self> {
         vec![
@@ -49,17 +71,13 @@ impl FavMode {
             FavMode::NavigateFlush,
             FavMode::Filter,
             FavMode::AssignNumber,
+            FavMode::Command,
+            FavMode::Jump,
+            FavMode::Renames,
+            FavMode::Presets,
             FavMode::Help,
         ]
     }
-    pub fn total_commands() -> usize {
-        let modes = FavMode::variants();
-        let mut total = 0;
-        for mode in modes {
-            total += mode.get_commands().len();
-        }
-        total
-    }
 }
 
 impl Display for FavMode {
@@ -67,7 +85,11 @@ impl Display for FavMode {
         match self {
             FavMode::NavigateFavs | FavMode::NavigateFlush => write!(f, "Navigate"),
             FavMode::Filter => write!(f, "Filter"),
+            FavMode::Presets => write!(f, "Presets"),
             FavMode::Help => write!(f, "Help"),
+            FavMode::Command => write!(f, "Command"),
+            FavMode::Jump => write!(f, "Jump"),
+            FavMode::Renames => write!(f, "Renamed Sessions"),
             FavMode::AssignNumber => write!(f, "Assign Number"),
         }
     }
@@ -1,30 +1,73 @@
-use zellij_tile::prelude::BareKey;
+use zellij_tile::prelude::KeyWithModifier;
 
-use crate::{favs::Favs, favs_mode::FavMode};
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    help::open_help,
+    keymap::{Action, KeyMatch},
+    navigate::ColumnCursor,
+    text_input::typed_char,
+};
 
-pub fn match_filter_key(ctx: &mut Favs, key: &BareKey) -> bool {
-    if let Some(filter) = ctx.filter.as_mut() {
-        match key {
-            BareKey::Char(char) => {
-                filter.push(*char);
-            }
-            BareKey::Backspace => {
-                filter.pop();
-            }
-            BareKey::Enter | BareKey::Left => {
-                ctx.mode = FavMode::NavigateFavs;
-                ctx.cursor = 0;
-            }
-            BareKey::Right | BareKey::Tab => {
-                ctx.mode = FavMode::NavigateFlush;
-                ctx.cursor = 0;
-            }
-            BareKey::Esc => {
-                ctx.filter = None;
-                ctx.mode = FavMode::NavigateFavs;
-            }
-            _ => return false,
+pub fn match_filter_key(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    let action = match ctx.resolve_key(key) {
+        KeyMatch::Action(action) => Some(action),
+        KeyMatch::Pending => return false,
+        KeyMatch::Hotkey(_) | KeyMatch::None => None,
+    };
+    let Some(filter) = ctx.filter.as_mut() else {
+        return true;
+    };
+
+    let Some(action) = action else {
+        let Some(c) = typed_char(key) else {
+            return false;
+        };
+        filter.insert(&c.to_string());
+        return true;
+    };
+
+    match action {
+        Action::DeleteWord => filter.delete_word(),
+        Action::ClearInput => filter.clear(),
+        Action::CursorStart => filter.home(),
+        Action::CursorEnd => filter.end(),
+        Action::CursorLeft => filter.left(),
+        Action::CursorRight => filter.right(),
+        Action::DeleteBack => filter.backspace(),
+        Action::DeleteForward => filter.delete(),
+        Action::ToggleContents => ctx.search_contents = !ctx.search_contents,
+        Action::HistoryPrev => ctx.recall_filter_history(true),
+        Action::HistoryNext => ctx.recall_filter_history(false),
+        Action::Confirm => {
+            ctx.push_filter_history();
+            ctx.mode = FavMode::NavigateFavs;
+            ctx.fav_cursor = ColumnCursor::default();
+            ctx.flush_cursor = ColumnCursor::default();
         }
+        Action::ConfirmFlush => {
+            ctx.push_filter_history();
+            ctx.mode = FavMode::NavigateFlush;
+            ctx.fav_cursor = ColumnCursor::default();
+            ctx.flush_cursor = ColumnCursor::default();
+        }
+        Action::InputHelp => open_help(ctx),
+        Action::Cancel => {
+            ctx.filter = None;
+            ctx.history_index = None;
+            ctx.mode = FavMode::NavigateFavs;
+        }
+        _ => return false,
+    }
+    true
+}
+
+pub fn match_filter_paste(ctx: &mut Favs, text: &str) -> bool {
+    if ctx.mode != FavMode::Filter {
+        return false;
+    }
+    if let Some(filter) = ctx.filter.as_mut() {
+        filter.insert(&text.replace(['\n', '\r'], " "));
     }
     true
 }
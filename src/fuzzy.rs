@@ -0,0 +1,134 @@
+const SCORE_MATCH: i64 = 16;
+const BONUS_FIRST_CHAR: i64 = 8;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 16;
+const PENALTY_GAP: i64 = 1;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    pub indices: Vec<usize>,
+}
+
+fn boundary_bonus(chars: &[char], idx: usize) -> i64 {
+    if idx == 0 {
+        return BONUS_FIRST_CHAR + BONUS_BOUNDARY;
+    }
+    let prev = chars[idx - 1];
+    let current = chars[idx];
+    if matches!(prev, '-' | '_' | ' ' | '.' | '/' | ':')
+        || prev.is_lowercase() && current.is_uppercase()
+    {
+        BONUS_BOUNDARY
+    } else {
+        0
+    }
+}
+
+/// Lowers a single char, keeping one char for those whose lowercase is longer (e.g. `İ`), so
+/// indices into the lowered text stay those of the original.
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Matches `pattern` as a case-insensitive subsequence of `text`, returning the best
+/// scoring alignment and the char indices of `text` it used.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().map(fold).collect();
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().copied().map(fold).collect();
+
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: vec![],
+        });
+    }
+    if pattern.len() > chars.len() {
+        return None;
+    }
+
+    // scores[i][j]: best score with pattern[i] matched at text[j], and where pattern[i - 1] was.
+    let mut scores: Vec<Vec<Option<(i64, usize)>>> = vec![vec![None; chars.len()]; pattern.len()];
+
+    for (i, pattern_char) in pattern.iter().enumerate() {
+        for j in i..chars.len() {
+            if lower[j] != *pattern_char {
+                continue;
+            }
+            let bonus = SCORE_MATCH + boundary_bonus(&chars, j);
+            if i == 0 {
+                scores[i][j] = Some((bonus - j as i64 * PENALTY_GAP, 0));
+                continue;
+            }
+            scores[i][j] = (i - 1..j)
+                .filter_map(|k| {
+                    scores[i - 1][k].map(|(score, _)| {
+                        let gap = if k + 1 == j {
+                            BONUS_CONSECUTIVE
+                        } else {
+                            -((j - k - 1) as i64) * PENALTY_GAP
+                        };
+                        (score + bonus + gap, k)
+                    })
+                })
+                .max_by_key(|(score, _)| *score);
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut idx, score) = scores[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, entry)| entry.map(|(score, _)| (j, score)))
+        .max_by_key(|(_, score)| *score)?;
+
+    let mut indices = vec![idx; pattern.len()];
+    for i in (1..pattern.len()).rev() {
+        idx = scores[i][idx].map(|(_, prev)| prev)?;
+        indices[i - 1] = idx;
+    }
+
+    Some(FuzzyMatch { score, indices })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indices(pattern: &str, text: &str) -> Option<Vec<usize>> {
+        fuzzy_match(pattern, text).map(|m| m.indices)
+    }
+
+    #[test]
+    fn matches_subsequences_ignoring_case() {
+        assert_eq!(indices("abc", "aXbXc"), Some(vec![0, 2, 4]));
+        assert_eq!(indices("ABC", "abc"), Some(vec![0, 1, 2]));
+        assert_eq!(indices("acb", "abc"), None);
+        assert_eq!(indices("abcd", "abc"), None);
+        assert_eq!(indices("", "abc"), Some(vec![]));
+    }
+
+    #[test]
+    fn prefers_consecutive_and_boundary_matches() {
+        assert_eq!(indices("api", "a-p-api"), Some(vec![4, 5, 6]));
+        assert_eq!(indices("fb", "foo-bar"), Some(vec![0, 4]));
+        assert_eq!(indices("fb", "fooBar"), Some(vec![0, 3]));
+    }
+
+    #[test]
+    fn scores_tighter_matches_higher() {
+        let score = |text| fuzzy_match("web", text).unwrap().score;
+        assert!(score("web") > score("w-e-b"));
+        assert!(score("web-api") > score("my-web"));
+        assert!(score("my-web") > score("awebb"));
+        assert!(score("w-e-b") > score("wxxxxexxxxb"));
+    }
+
+    #[test]
+    fn indices_count_chars_with_long_lowercase() {
+        assert_eq!(indices("İx", "aİx"), Some(vec![1, 2]));
+        assert_eq!(indices("ix", "aİx"), Some(vec![1, 2]));
+        assert_eq!(indices("éx", "ÉÉx"), Some(vec![1, 2]));
+    }
+}
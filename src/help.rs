@@ -1,13 +1,207 @@
-use zellij_tile::prelude::BareKey;
+use zellij_tile::prelude::{BareKey, KeyWithModifier};
 
-use crate::{favs::Favs, favs_mode::FavMode};
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    keymap::{Action, KeyMatch, Keymap},
+    text_input::{typed_char, TextInput},
+    theme::{Line, Role},
+};
 
-pub fn match_help_keys(ctx: &mut Favs, key: &BareKey) -> bool {
-    match key {
-        BareKey::Esc | BareKey::Char('q') => {
-            ctx.mode = FavMode::NavigateFavs;
+pub enum HelpRow {
+    Section(FavMode),
+    Command(String, String),
+}
+
+/// State of the help screen, remembering where it was opened from.
+pub struct HelpView {
+    pub previous_mode: FavMode,
+    pub offset: usize,
+    pub search: TextInput,
+    pub searching: bool,
+}
+
+impl HelpView {
+    /// Opens the help scrolled to the section of `previous_mode`.
+    pub fn new(previous_mode: FavMode, keymap: &Keymap) -> Self {
+        let section = previous_mode.to_string();
+        let offset = help_rows(keymap, "")
+            .iter()
+            .position(|row| matches!(row, HelpRow::Section(mode) if mode.to_string() == section))
+            .unwrap_or(0);
+        HelpView {
+            previous_mode,
+            offset,
+            search: TextInput::default(),
+            searching: false,
+        }
+    }
+}
+
+/// Sections and bindings of every mode, keeping only the bindings whose keys or description
+/// contain `search` and the sections that still have bindings.
+pub fn help_rows(keymap: &Keymap, search: &str) -> Vec<HelpRow> {
+    let search = search.to_lowercase();
+    let mut rows = vec![];
+    for mode in FavMode::variants() {
+        if mode == FavMode::NavigateFlush {
+            continue;
+        }
+        let commands: Vec<HelpRow> = mode
+            .clone()
+            .get_commands(keymap)
+            .into_iter()
+            .filter(|(keys, description)| {
+                keys.to_lowercase().contains(&search)
+                    || description.to_lowercase().contains(&search)
+            })
+            .map(|(keys, description)| HelpRow::Command(keys, description))
+            .collect();
+        if !commands.is_empty() {
+            rows.push(HelpRow::Section(mode));
+            rows.extend(commands);
+        }
+    }
+    rows
+}
+
+pub fn open_help(ctx: &mut Favs) {
+    ctx.help = Some(HelpView::new(ctx.mode.clone(), &ctx.keymap));
+    ctx.mode = FavMode::Help;
+}
+
+fn close_help(ctx: &mut Favs) {
+    if let Some(help) = ctx.help.take() {
+        ctx.mode = help.previous_mode;
+    } else {
+        ctx.mode = FavMode::NavigateFavs;
+    }
+}
+
+fn help_space(ctx: &Favs) -> usize {
+    ctx.rows.saturating_sub(1).max(1)
+}
+
+pub fn match_help_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    let page = help_space(ctx);
+    let searching = ctx.help.as_ref().is_some_and(|help| help.searching);
+    let action = if searching {
+        None
+    } else {
+        match ctx.resolve_key(key) {
+            KeyMatch::Action(action) => Some(action),
+            KeyMatch::Pending | KeyMatch::Hotkey(_) | KeyMatch::None => return false,
+        }
+    };
+    let max_offset = ctx
+        .help
+        .as_ref()
+        .map(|help| help_rows(&ctx.keymap, &help.search.value).len())
+        .unwrap_or(0)
+        .saturating_sub(page);
+    let Some(help) = ctx.help.as_mut() else {
+        close_help(ctx);
+        return true;
+    };
+
+    let Some(action) = action else {
+        match (typed_char(key), key.bare_key) {
+            (Some(c), _) => help.search.insert(&c.to_string()),
+            (_, BareKey::Backspace) => help.search.backspace(),
+            (_, BareKey::Delete) => help.search.delete(),
+            (_, BareKey::Left) => help.search.left(),
+            (_, BareKey::Right) => help.search.right(),
+            (_, BareKey::Enter) => help.searching = false,
+            (_, BareKey::Esc) => {
+                help.search.clear();
+                help.searching = false;
+            }
+            _ => return false,
+        }
+        help.offset = 0;
+        return true;
+    };
+
+    match action {
+        Action::MoveDown => help.offset += 1,
+        Action::MoveUp => help.offset = help.offset.saturating_sub(1),
+        Action::PageDown => help.offset += page,
+        Action::PageUp => help.offset = help.offset.saturating_sub(page),
+        Action::First => help.offset = 0,
+        Action::Last => help.offset = max_offset,
+        Action::Search => help.searching = true,
+        Action::Close if !help.search.value.is_empty() => help.search.clear(),
+        Action::Close | Action::Help => {
+            close_help(ctx);
+            return true;
         }
-        _ => {}
+        _ => return false,
     }
+    help.offset = help.offset.min(max_offset);
     true
 }
+
+pub fn render_help(ctx: &Favs, rows: usize, cols: usize) {
+    let Some(help) = ctx.help.as_ref() else {
+        return;
+    };
+    if rows == 0 {
+        return;
+    }
+    let help_rows = help_rows(&ctx.keymap, &help.search.value);
+    let keys_width = help_rows
+        .iter()
+        .filter_map(|row| match row {
+            HelpRow::Command(keys, _) => Some(keys.chars().count()),
+            HelpRow::Section(_) => None,
+        })
+        .max()
+        .unwrap_or(0)
+        .min(cols / 2);
+
+    let space = help_space(ctx);
+    for (y, row) in help_rows.iter().skip(help.offset).take(space).enumerate() {
+        let line = match row {
+            HelpRow::Section(mode) => Line::new().styled(mode, Role::Section),
+            HelpRow::Command(keys, description) => Line::new()
+                .plain("  ")
+                .styled(format!("{:<1$}", keys, keys_width), Role::Key)
+                .plain(format!("  {}", description)),
+        };
+        ctx.theme.print(&line, 0, y, Some(cols));
+    }
+
+    if rows < 2 {
+        return;
+    }
+    let footer = if help.searching {
+        let (before, current, after) = help.search.split_at_cursor();
+        Line::new()
+            .styled("/", Role::Prompt)
+            .plain(before)
+            .styled(current.unwrap_or('_'), Role::Prompt)
+            .plain(after)
+    } else if help_rows.is_empty() {
+        Line::new().styled(
+            format!("No bindings match '{}'", help.search.value),
+            Role::Error,
+        )
+    } else {
+        let mut line = Line::new();
+        if !help.search.value.is_empty() {
+            line = line
+                .styled(format!("/{}", help.search.value), Role::Prompt)
+                .plain("  ");
+        }
+        let shown = (help.offset + space).min(help_rows.len());
+        line.plain(format!(
+            "{}-{} of {}  {} search  {} close",
+            help.offset + 1,
+            shown,
+            help_rows.len(),
+            ctx.keymap.label(&Action::Search),
+            ctx.keymap.label(&Action::Close),
+        ))
+    };
+    ctx.theme.print(&footer, 0, rows - 1, Some(cols));
+}
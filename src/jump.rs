@@ -0,0 +1,168 @@
+use zellij_tile::prelude::KeyWithModifier;
+
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    help::open_help,
+    keymap::{Action, KeyMatch},
+    layout::sessions_space,
+    reload::{defer, Deferred, SwitchTarget},
+    text_input::typed_char,
+    theme::{Line, Role},
+};
+
+/// Keys used for labels, easiest to reach first.
+const LABEL_KEYS: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+/// `count` distinct labels, one key each while there are enough keys, otherwise two.
+fn jump_labels(count: usize) -> Vec<String> {
+    let keys: Vec<char> = LABEL_KEYS.chars().collect();
+    if count <= keys.len() {
+        return keys.iter().take(count).map(|c| c.to_string()).collect();
+    }
+    keys.iter()
+        .flat_map(|first| {
+            keys.iter()
+                .map(move |second| format!("{}{}", first, second))
+        })
+        .take(count)
+        .collect()
+}
+
+/// Labels every session visible in both columns, favorites first.
+pub fn open_jump(ctx: &mut Favs) {
+    let layout = ctx.get_layout();
+    let (fav_sessions, flush_sessions) = ctx.get_filtered_sessions();
+    let visible: Vec<String> = [
+        (fav_sessions, FavMode::NavigateFavs),
+        (flush_sessions, FavMode::NavigateFlush),
+    ]
+    .into_iter()
+    .flat_map(|(sessions, column)| {
+        let offset = ctx.column_cursor(&column).offset;
+        let space = sessions_space(&layout.column(&column));
+        sessions
+            .into_iter()
+            .skip(offset)
+            .take(space)
+            .map(|session| session.name)
+    })
+    .collect();
+    if visible.is_empty() {
+        return;
+    }
+    ctx.jump_labels = jump_labels(visible.len())
+        .into_iter()
+        .zip(visible)
+        .collect();
+    ctx.jump_input.clear();
+    ctx.current_column = Some(ctx.mode.clone());
+    ctx.mode = FavMode::Jump;
+}
+
+fn close_jump(ctx: &mut Favs) {
+    ctx.jump_labels.clear();
+    ctx.jump_input.clear();
+    ctx.mode = ctx.current_column.take().unwrap_or(FavMode::NavigateFavs);
+}
+
+pub fn match_jump_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    match ctx.resolve_key(key) {
+        KeyMatch::Action(Action::Cancel) => {
+            close_jump(ctx);
+            return true;
+        }
+        KeyMatch::Action(Action::Help) => {
+            open_help(ctx);
+            return true;
+        }
+        _ => {}
+    }
+    let Some(c) = typed_char(key) else {
+        return false;
+    };
+    ctx.jump_input.push(c);
+    let target = ctx
+        .jump_labels
+        .iter()
+        .find(|(label, _)| label == &ctx.jump_input)
+        .map(|(_, name)| name.clone());
+    if let Some(name) = target {
+        close_jump(ctx);
+        defer(ctx, Deferred::Switch(SwitchTarget::Session(name)));
+    } else if !ctx
+        .jump_labels
+        .iter()
+        .any(|(label, _)| label.starts_with(&ctx.jump_input))
+    {
+        close_jump(ctx);
+    }
+    true
+}
+
+/// The label of `name` while jumping, with the keys typed so far highlighted. Sessions whose
+/// label no longer matches get a blank one, so names stay aligned.
+pub fn jump_label(ctx: &Favs, name: &str) -> Option<Line> {
+    if ctx.mode != FavMode::Jump {
+        return None;
+    }
+    let (label, _) = ctx
+        .jump_labels
+        .iter()
+        .find(|(_, session)| session == name)?;
+    let width = label.chars().count();
+    let Some(rest) = label.strip_prefix(&ctx.jump_input) else {
+        return Some(Line::new().plain(format!("{:1$} ", "", width)));
+    };
+    Some(
+        Line::new()
+            .styled(&ctx.jump_input, Role::Prompt)
+            .styled(rest, Role::Match)
+            .plain(" "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_reachable(labels: &[String]) {
+        for (i, label) in labels.iter().enumerate() {
+            for (j, other) in labels.iter().enumerate() {
+                assert!(
+                    i == j || !other.starts_with(label.as_str()),
+                    "'{}' is a prefix of '{}'",
+                    label,
+                    other
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn uses_single_keys_while_they_last() {
+        assert!(jump_labels(0).is_empty());
+        assert_eq!(jump_labels(3), vec!["a", "s", "d"]);
+        let labels = jump_labels(LABEL_KEYS.len());
+        assert_eq!(labels.len(), LABEL_KEYS.len());
+        assert!(labels.iter().all(|label| label.chars().count() == 1));
+        assert_reachable(&labels);
+    }
+
+    #[test]
+    fn uses_two_keys_beyond_that() {
+        let labels = jump_labels(LABEL_KEYS.len() + 1);
+        assert_eq!(labels.len(), LABEL_KEYS.len() + 1);
+        assert!(labels.iter().all(|label| label.chars().count() == 2));
+        assert_eq!(labels[..3], ["aa", "as", "ad"]);
+        assert_reachable(&labels);
+    }
+
+    #[test]
+    fn labels_are_distinct_and_capped() {
+        let keys = LABEL_KEYS.len();
+        let labels = jump_labels(keys * keys + 10);
+        assert_eq!(labels.len(), keys * keys);
+        assert_reachable(&labels);
+    }
+}
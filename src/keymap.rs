@@ -0,0 +1,765 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use zellij_tile::prelude::{BareKey, KeyModifier, KeyWithModifier};
+
+use crate::favs_mode::FavMode;
+
+/// A sequence of keys, usually a single one, e.g. `Ctrl d` or `g g`.
+pub type Binding = Vec<KeyWithModifier>;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Action {
+    Open,
+    MoveSession,
+    SwitchColumn,
+    FocusFavs,
+    FocusFlush,
+    MoveDown,
+    MoveUp,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Assign,
+    Back,
+    Next,
+    Prev,
+    ToggleCounters,
+    ToggleDetails,
+    Filter,
+    Presets,
+    Flush,
+    Help,
+    /// Help from modes where `?` is typed as text.
+    InputHelp,
+    Close,
+    Confirm,
+    ConfirmFlush,
+    Cancel,
+    CursorLeft,
+    CursorRight,
+    CursorStart,
+    CursorEnd,
+    DeleteBack,
+    DeleteForward,
+    DeleteWord,
+    ClearInput,
+    HistoryPrev,
+    HistoryNext,
+    ToggleContents,
+    SavePreset,
+    DeletePreset,
+    Search,
+    ToggleMark,
+    MarkAll,
+    InvertMarks,
+    Kill,
+    Delete,
+    Tag,
+    Export,
+    Undo,
+    Command,
+    Complete,
+    Rename,
+    Jump,
+    Renames,
+    Accept,
+    Reject,
+}
+
+impl Action {
+    /// Actions available in `mode`, in the order they are listed in the help.
+    pub fn for_mode(mode: &FavMode) -> &'static [Action] {
+        match mode {
+            FavMode::NavigateFavs | FavMode::NavigateFlush => &[
+                Action::Open,
+                Action::MoveSession,
+                Action::SwitchColumn,
+                Action::FocusFavs,
+                Action::FocusFlush,
+                Action::MoveDown,
+                Action::MoveUp,
+                Action::PageDown,
+                Action::PageUp,
+                Action::First,
+                Action::Last,
+                Action::Assign,
+                Action::Jump,
+                Action::Back,
+                Action::Next,
+                Action::Prev,
+                Action::ToggleCounters,
+                Action::ToggleDetails,
+                Action::Filter,
+                Action::Presets,
+                Action::Flush,
+                Action::ToggleMark,
+                Action::MarkAll,
+                Action::InvertMarks,
+                Action::Kill,
+                Action::Delete,
+                Action::Tag,
+                Action::Export,
+                Action::Undo,
+                Action::Rename,
+                Action::Renames,
+                Action::Command,
+                Action::Help,
+                Action::Close,
+            ],
+            FavMode::Filter => &[
+                Action::Confirm,
+                Action::ConfirmFlush,
+                Action::Cancel,
+                Action::CursorLeft,
+                Action::CursorRight,
+                Action::CursorStart,
+                Action::CursorEnd,
+                Action::DeleteBack,
+                Action::DeleteForward,
+                Action::DeleteWord,
+                Action::ClearInput,
+                Action::HistoryPrev,
+                Action::HistoryNext,
+                Action::ToggleContents,
+                Action::InputHelp,
+            ],
+            FavMode::Command => &[
+                Action::Confirm,
+                Action::Cancel,
+                Action::Complete,
+                Action::CursorLeft,
+                Action::CursorRight,
+                Action::CursorStart,
+                Action::CursorEnd,
+                Action::DeleteBack,
+                Action::DeleteForward,
+                Action::DeleteWord,
+                Action::ClearInput,
+                Action::InputHelp,
+            ],
+            FavMode::Jump => &[Action::Cancel, Action::Help],
+            FavMode::Renames => &[Action::Accept, Action::Reject, Action::Cancel, Action::Help],
+            FavMode::AssignNumber => &[
+                Action::Confirm,
+                Action::DeleteBack,
+                Action::Cancel,
+                Action::InputHelp,
+            ],
+            FavMode::Presets => &[
+                Action::Confirm,
+                Action::MoveDown,
+                Action::MoveUp,
+                Action::SavePreset,
+                Action::DeletePreset,
+                Action::Help,
+                Action::Close,
+            ],
+            FavMode::Help => &[
+                Action::MoveDown,
+                Action::MoveUp,
+                Action::PageDown,
+                Action::PageUp,
+                Action::First,
+                Action::Last,
+                Action::Search,
+                Action::Help,
+                Action::Close,
+            ],
+        }
+    }
+    /// Name used to remap the action from the configuration, as `bind_<name>`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Open => "open",
+            Action::MoveSession => "move_session",
+            Action::SwitchColumn => "switch_column",
+            Action::FocusFavs => "focus_favs",
+            Action::FocusFlush => "focus_flush",
+            Action::MoveDown => "move_down",
+            Action::MoveUp => "move_up",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Assign => "assign",
+            Action::Back => "back",
+            Action::Next => "next",
+            Action::Prev => "prev",
+            Action::ToggleCounters => "toggle_counters",
+            Action::ToggleDetails => "toggle_details",
+            Action::Filter => "filter",
+            Action::Presets => "presets",
+            Action::Flush => "flush",
+            Action::Help => "help",
+            Action::InputHelp => "input_help",
+            Action::Close => "close",
+            Action::Confirm => "confirm",
+            Action::ConfirmFlush => "confirm_flush",
+            Action::Cancel => "cancel",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::CursorStart => "cursor_start",
+            Action::CursorEnd => "cursor_end",
+            Action::DeleteBack => "delete_back",
+            Action::DeleteForward => "delete_forward",
+            Action::DeleteWord => "delete_word",
+            Action::ClearInput => "clear_input",
+            Action::HistoryPrev => "history_prev",
+            Action::HistoryNext => "history_next",
+            Action::ToggleContents => "toggle_contents",
+            Action::SavePreset => "save_preset",
+            Action::DeletePreset => "delete_preset",
+            Action::Search => "search",
+            Action::ToggleMark => "toggle_mark",
+            Action::MarkAll => "mark_all",
+            Action::InvertMarks => "invert_marks",
+            Action::Kill => "kill",
+            Action::Delete => "delete",
+            Action::Tag => "tag",
+            Action::Export => "export",
+            Action::Undo => "undo",
+            Action::Command => "command",
+            Action::Complete => "complete",
+            Action::Rename => "rename",
+            Action::Jump => "jump",
+            Action::Renames => "renames",
+            Action::Accept => "accept",
+            Action::Reject => "reject",
+        }
+    }
+    pub fn description(&self, mode: &FavMode) -> &'static str {
+        match (self, mode) {
+            (Action::Open, _) => "Open session",
+            (Action::MoveSession, _) => "Move session to Flush/Favorites",
+            (Action::SwitchColumn, _) => "Navigate Flush/Favorites items",
+            (Action::FocusFavs, _) => "Go to Favorites",
+            (Action::FocusFlush, _) => "Go to Flush",
+            (Action::MoveDown, FavMode::Help) => "Scroll down",
+            (Action::MoveUp, FavMode::Help) => "Scroll up",
+            (Action::MoveDown, _) => "Move cursor down",
+            (Action::MoveUp, _) => "Move cursor up",
+            (Action::PageDown, _) => "Page down",
+            (Action::PageUp, _) => "Page up",
+            (Action::First, FavMode::Help) => "Go to top",
+            (Action::Last, FavMode::Help) => "Go to bottom",
+            (Action::First, _) => "Go to first session",
+            (Action::Last, _) => "Go to last session",
+            (Action::Assign, _) => "Add quick access number",
+            (Action::Back, _) => "Go back to previous session",
+            (Action::Next, _) => "Switch to next favorite",
+            (Action::Prev, _) => "Switch to previous favorite",
+            (Action::ToggleCounters, _) => "Toggle tabs & panes counter",
+            (Action::ToggleDetails, _) => "Toggle session details",
+            (Action::Filter, _) => "Filter",
+            (Action::Presets, _) => "Filter presets",
+            (Action::Flush, _) => "Delete Flush Sessions",
+            (Action::Help, FavMode::Help) => "Close help",
+            (Action::Help | Action::InputHelp, _) => "Help",
+            (Action::Close, FavMode::Help) => "Close help",
+            (Action::Close, FavMode::Presets) => "Close presets",
+            (Action::Close, _) => "Close",
+            (Action::Confirm, FavMode::Presets) => "Apply preset",
+            (Action::Confirm, FavMode::Command) => "Run command",
+            (Action::Confirm, FavMode::AssignNumber) => "Assign typed hotkey",
+            (Action::Confirm, _) => "Use filter and go to Favs",
+            (Action::ConfirmFlush, _) => "Use filter and go to Flush",
+            (Action::Cancel, FavMode::Filter) => "Close filter",
+            (Action::Cancel, FavMode::Command) => "Close command palette",
+            (Action::Cancel, FavMode::Jump) => "Cancel jump",
+            (Action::Cancel, FavMode::Renames) => "Answer later",
+            (Action::Cancel, _) => "Close",
+            (Action::CursorLeft, _) => "Move cursor left",
+            (Action::CursorRight, _) => "Move cursor right",
+            (Action::CursorStart, _) => "Go to start",
+            (Action::CursorEnd, _) => "Go to end",
+            (Action::DeleteBack, _) => "Delete previous character",
+            (Action::DeleteForward, _) => "Delete next character",
+            (Action::DeleteWord, _) => "Delete word",
+            (Action::ClearInput, FavMode::Command) => "Clear command",
+            (Action::ClearInput, _) => "Clear filter",
+            (Action::HistoryPrev, _) => "Previous filter from history",
+            (Action::HistoryNext, _) => "Next filter from history",
+            (Action::ToggleContents, _) => "Toggle searching tabs, panes & commands",
+            (Action::SavePreset, _) => "Save current filter as preset",
+            (Action::DeletePreset, _) => "Delete preset",
+            (Action::Search, _) => "Search bindings",
+            (Action::ToggleMark, _) => "Mark/unmark session",
+            (Action::MarkAll, _) => "Mark/unmark all visible sessions",
+            (Action::InvertMarks, _) => "Invert marks",
+            (Action::Kill, _) => "Kill marked sessions",
+            (Action::Delete, _) => "Delete marked sessions",
+            (Action::Tag, _) => "Tag marked sessions",
+            (Action::Export, _) => "Export marked sessions",
+            (Action::Undo, _) => "Undo last change",
+            (Action::Command, _) => "Command palette",
+            (Action::Complete, _) => "Complete command, session or argument",
+            (Action::Rename, _) => "Rename current session",
+            (Action::Jump, _) => "Jump to a session by its label",
+            (Action::Renames, _) => "Review sessions renamed outside the plugin",
+            (Action::Accept, _) => "Yes, keep the metadata",
+            (Action::Reject, _) => "No, treat it as a new session",
+        }
+    }
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Open => &["Enter"],
+            Action::MoveSession => &["Space"],
+            Action::SwitchColumn => &["Tab"],
+            Action::FocusFavs => &["h", "Left"],
+            Action::FocusFlush => &["l", "Right"],
+            Action::MoveDown => &["j", "Down"],
+            Action::MoveUp => &["k", "Up"],
+            Action::PageDown => &["PageDown", "Ctrl d"],
+            Action::PageUp => &["PageUp", "Ctrl u"],
+            Action::First => &["Home", "g g"],
+            Action::Last => &["End", "G"],
+            Action::Assign => &["a"],
+            Action::Back => &["b"],
+            Action::Next => &["n"],
+            Action::Prev => &["p"],
+            Action::ToggleCounters => &["t"],
+            Action::ToggleDetails => &["i"],
+            Action::Filter => &["/"],
+            Action::Presets => &["P"],
+            Action::Flush => &["f"],
+            Action::Help => &["?", "F1"],
+            Action::InputHelp => &["F1"],
+            Action::Close => &["Esc", "q"],
+            Action::Confirm => &["Enter"],
+            Action::ConfirmFlush => &["Tab"],
+            Action::Cancel => &["Esc"],
+            Action::CursorLeft => &["Left"],
+            Action::CursorRight => &["Right"],
+            Action::CursorStart => &["Home", "Ctrl a"],
+            Action::CursorEnd => &["End", "Ctrl e"],
+            Action::DeleteBack => &["Backspace"],
+            Action::DeleteForward => &["Delete"],
+            Action::DeleteWord => &["Ctrl w"],
+            Action::ClearInput => &["Ctrl u"],
+            Action::HistoryPrev => &["Up"],
+            Action::HistoryNext => &["Down"],
+            Action::ToggleContents => &["Ctrl f"],
+            Action::SavePreset => &["s"],
+            Action::DeletePreset => &["d"],
+            Action::Search => &["/"],
+            Action::ToggleMark => &["v"],
+            Action::MarkAll => &["V"],
+            Action::InvertMarks => &["*"],
+            Action::Kill => &["x"],
+            Action::Delete => &["D"],
+            Action::Tag => &["#"],
+            Action::Export => &["E"],
+            Action::Undo => &["u"],
+            Action::Command => &[":"],
+            Action::Complete => &["Tab"],
+            Action::Rename => &["r"],
+            Action::Jump => &["s"],
+            Action::Renames => &["!"],
+            Action::Accept => &["y"],
+            Action::Reject => &["n"],
+        }
+    }
+    fn variants() -> Vec<Self> {
+        let mut actions: Vec<Action> = FavMode::variants()
+            .iter()
+            .flat_map(|mode| Action::for_mode(mode).iter().copied())
+            .collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+}
+
+/// Drops Shift from symbols such as `?`, where it is already part of the character and only
+/// some terminals report it.
+pub fn normalize_key(key: &KeyWithModifier) -> KeyWithModifier {
+    let mut key = key.clone();
+    if let BareKey::Char(c) = key.bare_key {
+        if !c.is_alphabetic() {
+            key.key_modifiers.remove(&KeyModifier::Shift);
+        }
+    }
+    key
+}
+
+/// Parses a binding such as `j`, `Ctrl d` or `g g`.
+pub fn parse_binding(value: &str) -> Result<Binding, String> {
+    let value = value.trim();
+    if let Ok(key) = KeyWithModifier::from_str(value) {
+        return Ok(vec![normalize_key(&key)]);
+    }
+    let binding = value
+        .split_whitespace()
+        .map(|key| {
+            KeyWithModifier::from_str(key)
+                .map(|key| normalize_key(&key))
+                .map_err(|_| format!("invalid key '{}'", value))
+        })
+        .collect::<Result<Binding, String>>()?;
+    if binding.is_empty() {
+        return Err("empty key".to_string());
+    }
+    Ok(binding)
+}
+
+/// Whether one binding is the other or starts it, so typing it would trigger both.
+pub fn bindings_overlap(binding: &[KeyWithModifier], other: &[KeyWithModifier]) -> bool {
+    binding.starts_with(other) || other.starts_with(binding)
+}
+
+fn key_label(key: &KeyWithModifier) -> String {
+    let bare_key = match key.bare_key {
+        BareKey::Char(' ') => "<Space>".to_string(),
+        BareKey::Char(c) => c.to_string(),
+        BareKey::Enter => "<Enter>".to_string(),
+        BareKey::Esc => "<Esc>".to_string(),
+        BareKey::Tab => "<Tab>".to_string(),
+        BareKey::Backspace => "<Backspace>".to_string(),
+        BareKey::Delete => "<Del>".to_string(),
+        BareKey::Home => "<Home>".to_string(),
+        BareKey::End => "<End>".to_string(),
+        BareKey::PageUp => "<PgUp>".to_string(),
+        BareKey::PageDown => "<PgDn>".to_string(),
+        ref bare_key => bare_key.to_string(),
+    };
+    key.key_modifiers
+        .iter()
+        .map(|modifier| format!("{}-", modifier.to_string()))
+        .chain(std::iter::once(bare_key))
+        .collect()
+}
+
+pub fn binding_label(binding: &Binding) -> String {
+    binding.iter().map(key_label).collect::<Vec<_>>().join(" ")
+}
+
+pub enum KeyMatch {
+    Action(Action),
+    /// The keys so far start a longer binding.
+    Pending,
+    /// The hotkey of the named session.
+    Hotkey(String),
+    None,
+}
+
+pub struct Keymap {
+    pub bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::variants()
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|keys| parse_binding(keys).ok())
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from `bind_<action> "<keys>"` entries, where keys are separated by `|`.
+    /// Returns the errors of entries that couldn't be used.
+    pub fn from_config(configuration: &BTreeMap<String, String>) -> (Self, Vec<String>) {
+        let mut keymap = Keymap::default();
+        let mut errors = vec![];
+        let mut configured = BTreeSet::new();
+        for action in Action::variants() {
+            let config_key = format!("bind_{}", action.name());
+            let Some(value) = configuration.get(&config_key) else {
+                continue;
+            };
+            match value.split('|').map(parse_binding).collect() {
+                Ok(bindings) => {
+                    keymap.bindings.insert(action, bindings);
+                    configured.insert(action);
+                }
+                Err(e) => errors.push(format!("{}: {}", config_key, e)),
+            }
+        }
+        // Configured keys take over from the defaults of other actions in the same mode. When two
+        // configured actions share keys, the one listed first in the mode wins.
+        for mode in FavMode::variants() {
+            let actions = Action::for_mode(&mode);
+            for (i, action) in actions.iter().enumerate() {
+                if !configured.contains(action) {
+                    continue;
+                }
+                let keys = keymap.bindings.get(action).cloned().unwrap_or_default();
+                for (j, other) in actions.iter().enumerate() {
+                    if i == j || j < i && configured.contains(other) {
+                        continue;
+                    }
+                    let overlaps = |binding: &Binding| {
+                        keys.iter().find(|keys| {
+                            keys.starts_with(binding.as_slice()) || binding.starts_with(keys)
+                        })
+                    };
+                    let Some(clash) = keymap
+                        .bindings
+                        .get(other)
+                        .into_iter()
+                        .flatten()
+                        .find_map(overlaps)
+                    else {
+                        continue;
+                    };
+                    let error = if configured.contains(other) {
+                        format!(
+                            "bind_{}: '{}' conflicts with bind_{}",
+                            action.name(),
+                            binding_label(clash),
+                            other.name()
+                        )
+                    } else {
+                        if let Some(bindings) = keymap.bindings.get_mut(other) {
+                            bindings.retain(|binding| overlaps(binding).is_none());
+                        }
+                        format!(
+                            "bind_{}: '{}' replaces the binding of {}",
+                            action.name(),
+                            binding_label(clash),
+                            other.name()
+                        )
+                    };
+                    if !errors.contains(&error) {
+                        errors.push(error);
+                    }
+                }
+            }
+        }
+        (keymap, errors)
+    }
+    pub fn resolve(&self, mode: &FavMode, keys: &[KeyWithModifier]) -> KeyMatch {
+        let mut pending = false;
+        for action in Action::for_mode(mode) {
+            for binding in self.bindings.get(action).into_iter().flatten() {
+                if binding.as_slice() == keys {
+                    return KeyMatch::Action(*action);
+                }
+                pending |= binding.starts_with(keys);
+            }
+        }
+        if pending {
+            KeyMatch::Pending
+        } else {
+            KeyMatch::None
+        }
+    }
+    /// The navigation action whose binding `keys` would hide, or be hidden by, if used as a
+    /// session hotkey.
+    pub fn navigation_conflict(&self, keys: &[KeyWithModifier]) -> Option<Action> {
+        Action::for_mode(&FavMode::NavigateFavs)
+            .iter()
+            .find(|action| {
+                self.bindings
+                    .get(action)
+                    .into_iter()
+                    .flatten()
+                    .any(|binding| bindings_overlap(binding, keys))
+            })
+            .copied()
+    }
+    /// Keys bound to `action`, formatted for the help.
+    pub fn label(&self, action: &Action) -> String {
+        self.bindings
+            .get(action)
+            .map(|bindings| {
+                bindings
+                    .iter()
+                    .map(binding_label)
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(value: &str) -> KeyWithModifier {
+        KeyWithModifier::from_str(value).unwrap()
+    }
+
+    fn config(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn binding(value: &str) -> Binding {
+        parse_binding(value).unwrap()
+    }
+
+    #[test]
+    fn overlaps_bindings_that_start_one_another() {
+        assert!(bindings_overlap(&binding("c"), &binding("c o")));
+        assert!(bindings_overlap(&binding("c o m"), &binding("c o")));
+        assert!(bindings_overlap(&binding("c o"), &binding("c o")));
+        assert!(!bindings_overlap(&binding("c o"), &binding("c e")));
+        assert!(!bindings_overlap(&binding("c"), &binding("Ctrl c")));
+        assert!(!bindings_overlap(&binding("o c"), &binding("c")));
+    }
+
+    #[test]
+    fn finds_hotkeys_clashing_with_navigation() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.navigation_conflict(&binding("j")),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(
+            keymap.navigation_conflict(&binding("g")),
+            Some(Action::First)
+        );
+        assert_eq!(
+            keymap.navigation_conflict(&binding("j z")),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(keymap.navigation_conflict(&binding("g a")), None);
+        assert_eq!(keymap.navigation_conflict(&binding("c o")), None);
+    }
+
+    #[test]
+    fn parses_single_keys_and_modifiers() {
+        assert_eq!(parse_binding("j"), Ok(vec![key("j")]));
+        assert_eq!(parse_binding(" Ctrl d "), Ok(vec![key("Ctrl d")]));
+        assert_eq!(parse_binding("Ctrl Alt x"), Ok(vec![key("Ctrl Alt x")]));
+        assert_eq!(parse_binding("F1"), Ok(vec![key("F1")]));
+        assert_eq!(parse_binding("Enter"), Ok(vec![key("Enter")]));
+    }
+
+    #[test]
+    fn parses_sequences() {
+        assert_eq!(parse_binding("g g"), Ok(vec![key("g"), key("g")]));
+        assert_eq!(
+            parse_binding("g Ctrl a"),
+            Err("invalid key 'g Ctrl a'".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_bindings() {
+        assert_eq!(parse_binding(""), Err("empty key".to_string()));
+        assert_eq!(parse_binding("nope"), Err("invalid key 'nope'".to_string()));
+    }
+
+    #[test]
+    fn normalizes_shifted_symbols_only() {
+        let shifted = |c| KeyWithModifier::new(BareKey::Char(c)).with_shift_modifier();
+        assert_eq!(normalize_key(&shifted('?')), key("?"));
+        assert_eq!(
+            normalize_key(&shifted('a')).key_modifiers,
+            shifted('a').key_modifiers
+        );
+        assert_eq!(normalize_key(&key("Ctrl d")), key("Ctrl d"));
+    }
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        let (_, errors) = Keymap::from_config(&BTreeMap::new());
+        assert!(errors.is_empty());
+        let keymap = Keymap::default();
+        for mode in FavMode::variants() {
+            let bindings: Vec<&Binding> = Action::for_mode(&mode)
+                .iter()
+                .flat_map(|action| keymap.bindings.get(action).into_iter().flatten())
+                .collect();
+            for (i, a) in bindings.iter().enumerate() {
+                for b in bindings.iter().skip(i + 1) {
+                    assert!(
+                        !a.starts_with(b) && !b.starts_with(a),
+                        "{} and {} overlap in {}",
+                        binding_label(a),
+                        binding_label(b),
+                        mode
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn configures_alternatives() {
+        let (keymap, errors) = Keymap::from_config(&config(&[("bind_move_down", "j | Ctrl n")]));
+        assert!(errors.is_empty());
+        assert_eq!(
+            keymap.bindings[&Action::MoveDown],
+            vec![vec![key("j")], vec![key("Ctrl n")]]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_bindings_and_keeps_defaults() {
+        let (keymap, errors) = Keymap::from_config(&config(&[("bind_move_down", "j | nope")]));
+        assert_eq!(errors, vec!["bind_move_down: invalid key 'nope'"]);
+        assert_eq!(
+            keymap.bindings[&Action::MoveDown],
+            Keymap::default().bindings[&Action::MoveDown]
+        );
+    }
+
+    #[test]
+    fn configured_keys_replace_default_bindings() {
+        let (keymap, errors) = Keymap::from_config(&config(&[("bind_jump", "f")]));
+        assert_eq!(errors, vec!["bind_jump: 'f' replaces the binding of flush"]);
+        assert!(keymap.bindings[&Action::Flush].is_empty());
+        assert!(matches!(
+            keymap.resolve(&FavMode::NavigateFavs, &[key("f")]),
+            KeyMatch::Action(Action::Jump)
+        ));
+
+        let (keymap, errors) = Keymap::from_config(&config(&[("bind_assign", "g")]));
+        assert_eq!(
+            errors,
+            vec!["bind_assign: 'g' replaces the binding of first"]
+        );
+        assert_eq!(keymap.label(&Action::First), "<Home>");
+    }
+
+    #[test]
+    fn reports_conflicting_configured_bindings() {
+        let (_, errors) = Keymap::from_config(&config(&[
+            ("bind_move_down", "x"),
+            ("bind_move_up", "x | k"),
+        ]));
+        assert_eq!(
+            errors,
+            vec![
+                "bind_move_down: 'x' conflicts with bind_move_up",
+                "bind_move_down: 'x' replaces the binding of kill",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_sequences() {
+        let keymap = Keymap::default();
+        let mode = FavMode::NavigateFavs;
+        assert!(matches!(
+            keymap.resolve(&mode, &[key("g")]),
+            KeyMatch::Pending
+        ));
+        assert!(matches!(
+            keymap.resolve(&mode, &[key("g"), key("g")]),
+            KeyMatch::Action(Action::First)
+        ));
+        assert!(matches!(
+            keymap.resolve(&mode, &[key("G")]),
+            KeyMatch::Action(Action::Last)
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::favs_mode::FavMode;
+
+const MIN_COLUMN_WIDTH: usize = 20;
+const MIN_ROWS_FOR_FOOTER: usize = 6;
+const MIN_COLS_FOR_SIDE_DETAILS: usize = 120;
+const MIN_ROWS_FOR_BOTTOM_DETAILS: usize = 24;
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Where each part of the navigation view goes. Each column is a title row followed by its
+/// session rows; the filter prompt always takes the first row.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct NavigationLayout {
+    pub favs: Rect,
+    pub flush: Rect,
+    pub details: Option<Rect>,
+    pub footer: Option<usize>,
+}
+
+impl NavigationLayout {
+    pub fn new(rows: usize, cols: usize, show_details: bool) -> Self {
+        let footer = (rows >= MIN_ROWS_FOR_FOOTER).then(|| rows - 1);
+        let top = rows.min(1);
+        let mut body = Rect {
+            x: 0,
+            y: top,
+            width: cols,
+            height: footer.unwrap_or(rows).saturating_sub(top),
+        };
+
+        let details = if show_details && cols >= MIN_COLS_FOR_SIDE_DETAILS {
+            let width = cols / 3;
+            body.width -= width;
+            Some(Rect {
+                x: body.width,
+                y: body.y,
+                width,
+                height: body.height,
+            })
+        } else if show_details && rows >= MIN_ROWS_FOR_BOTTOM_DETAILS {
+            let height = body.height / 2;
+            body.height -= height;
+            Some(Rect {
+                x: 0,
+                y: body.y + body.height,
+                width: cols,
+                height,
+            })
+        } else {
+            None
+        };
+
+        let (favs, flush) = if body.width >= MIN_COLUMN_WIDTH * 2 {
+            let width = body.width / 2;
+            (
+                Rect { width, ..body },
+                Rect {
+                    x: body.x + width,
+                    width: body.width - width,
+                    ..body
+                },
+            )
+        } else {
+            let height = body.height.div_ceil(2);
+            (
+                Rect { height, ..body },
+                Rect {
+                    y: body.y + height,
+                    height: body.height - height,
+                    ..body
+                },
+            )
+        };
+
+        NavigationLayout {
+            favs,
+            flush,
+            details,
+            footer,
+        }
+    }
+    pub fn column(&self, column: &FavMode) -> Rect {
+        match column {
+            FavMode::NavigateFlush => self.flush,
+            _ => self.favs,
+        }
+    }
+}
+
+/// Number of session rows a column can show below its title.
+pub fn sessions_space(rect: &Rect) -> usize {
+    rect.height.saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_fits(layout: &NavigationLayout, rows: usize, cols: usize) {
+        let rects = [Some(layout.favs), Some(layout.flush), layout.details];
+        for rect in rects.iter().flatten() {
+            assert!(
+                rect.x + rect.width <= cols,
+                "{:?} is wider than {}",
+                rect,
+                cols
+            );
+            assert!(
+                rect.y + rect.height <= rows,
+                "{:?} is taller than {}",
+                rect,
+                rows
+            );
+            assert!(rect.y >= rows.min(1), "{:?} covers the filter prompt", rect);
+            if let Some(footer) = layout.footer {
+                assert!(
+                    rect.y + rect.height <= footer,
+                    "{:?} covers the footer",
+                    rect
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tiny_panes_have_no_footer() {
+        for rows in 0..MIN_ROWS_FOR_FOOTER {
+            let layout = NavigationLayout::new(rows, 80, false);
+            assert_eq!(layout.footer, None);
+            assert_eq!(layout.favs.height, rows.saturating_sub(1));
+            assert_eq!(layout.favs.height + layout.favs.y, rows);
+            assert_fits(&layout, rows, 80);
+        }
+        let layout = NavigationLayout::new(MIN_ROWS_FOR_FOOTER, 80, false);
+        assert_eq!(layout.footer, Some(MIN_ROWS_FOR_FOOTER - 1));
+        assert_fits(&layout, MIN_ROWS_FOR_FOOTER, 80);
+    }
+
+    #[test]
+    fn stacks_columns_below_two_column_widths() {
+        let narrow = NavigationLayout::new(20, MIN_COLUMN_WIDTH * 2 - 1, false);
+        assert_eq!(narrow.favs.x, narrow.flush.x);
+        assert_eq!(narrow.flush.y, narrow.favs.y + narrow.favs.height);
+        assert_eq!(narrow.favs.height + narrow.flush.height, 18);
+        assert_fits(&narrow, 20, MIN_COLUMN_WIDTH * 2 - 1);
+
+        for cols in [MIN_COLUMN_WIDTH * 2, MIN_COLUMN_WIDTH * 2 + 1] {
+            let wide = NavigationLayout::new(20, cols, false);
+            assert_eq!(wide.favs.y, wide.flush.y);
+            assert_eq!(wide.flush.x, wide.favs.width);
+            assert_eq!(wide.favs.width + wide.flush.width, cols);
+            assert!(wide.favs.width >= MIN_COLUMN_WIDTH);
+            assert_fits(&wide, 20, cols);
+        }
+    }
+
+    #[test]
+    fn places_details_beside_or_below() {
+        let side = NavigationLayout::new(30, MIN_COLS_FOR_SIDE_DETAILS, true);
+        let details = side.details.unwrap();
+        assert_eq!(details.x, side.flush.x + side.flush.width);
+        assert_eq!(details.height, side.favs.height);
+        assert_fits(&side, 30, MIN_COLS_FOR_SIDE_DETAILS);
+
+        let below = NavigationLayout::new(MIN_ROWS_FOR_BOTTOM_DETAILS, 60, true);
+        let details = below.details.unwrap();
+        assert_eq!(details.y, below.favs.y + below.favs.height);
+        assert_eq!(details.width, 60);
+        assert_fits(&below, MIN_ROWS_FOR_BOTTOM_DETAILS, 60);
+
+        let hidden = NavigationLayout::new(MIN_ROWS_FOR_BOTTOM_DETAILS - 1, 60, true);
+        assert_eq!(hidden.details, None);
+        assert_eq!(
+            hidden,
+            NavigationLayout::new(MIN_ROWS_FOR_BOTTOM_DETAILS - 1, 60, false)
+        );
+    }
+}
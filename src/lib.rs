@@ -1,34 +1,273 @@
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
+use zellij_tile::prelude::{BareKey, KeyModifier, KeyWithModifier, SessionInfo};
 
 pub mod assign_number;
+pub mod bar;
+pub mod commands;
+pub mod details;
 pub mod favs;
 pub mod favs_mode;
 pub mod filter;
+pub mod fuzzy;
 pub mod help;
+pub mod jump;
+pub mod keymap;
+pub mod layout;
+pub mod mouse;
 pub mod navigate;
+pub mod palette;
+pub mod pipe;
+pub mod presets;
+pub mod query;
+pub mod reload;
+pub mod renames;
+pub mod sort;
+pub mod status;
+pub mod text_input;
+pub mod theme;
+pub mod ui_state;
+pub mod undo;
+
+/// Number of quick access numbers in each bank.
+pub const BANK_SIZE: u8 = 10;
+
+/// Banks of quick access numbers, each opened with a digit and the given modifiers. A number is
+/// stored as `bank * BANK_SIZE + digit`.
+pub const BANKS: &[(&str, &[KeyModifier])] = &[
+    ("", &[]),
+    ("Alt", &[KeyModifier::Alt]),
+    ("Ctrl", &[KeyModifier::Ctrl]),
+    ("Ctrl-Alt", &[KeyModifier::Ctrl, KeyModifier::Alt]),
+];
+
+/// Formats a quick access number, e.g. `3`, or `Alt-3` for the second bank.
+pub fn number_label(number: u8) -> String {
+    let digit = number % BANK_SIZE;
+    match BANKS.get((number / BANK_SIZE) as usize) {
+        Some((bank, _)) if !bank.is_empty() => format!("{}-{}", bank, digit),
+        _ => digit.to_string(),
+    }
+}
+
+/// Quotes a value for bash, so it is passed on as a single word whatever it contains.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Quotes a path for bash, leaving a leading `~/` outside the quotes so it still expands.
+pub fn shell_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", shell_quote(rest)),
+        None => shell_quote(path),
+    }
+}
+
+/// Quick access number typed with a digit key, in the bank of the modifiers held.
+pub fn key_number(key: &KeyWithModifier) -> Option<u8> {
+    let BareKey::Char(c) = key.bare_key else {
+        return None;
+    };
+    let digit = c.to_digit(10)? as u8;
+    let bank = BANKS.iter().position(|(_, modifiers)| {
+        key.key_modifiers.len() == modifiers.len()
+            && modifiers.iter().all(|m| key.key_modifiers.contains(m))
+    })?;
+    Some(bank as u8 * BANK_SIZE + digit)
+}
+
+/// Parses a quick access number written as `3`, `alt-3`, `ctrl-3` or `ctrl-alt-3`.
+pub fn parse_number(value: &str) -> Option<u8> {
+    let value = value.trim().to_lowercase();
+    BANKS.iter().enumerate().find_map(|(bank, (label, _))| {
+        let digit = if label.is_empty() {
+            value.as_str()
+        } else {
+            value.strip_prefix(&format!("{}-", label.to_lowercase()))?
+        };
+        match digit.parse::<u8>() {
+            Ok(digit) if digit < BANK_SIZE => Some(bank as u8 * BANK_SIZE + digit),
+            _ => None,
+        }
+    })
+}
 
 #[derive(Default, Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct FavSessionInfo {
     pub name: String,
     pub is_active: bool,
     pub assigned_number: Option<u8>,
+    /// Keys opening the session, e.g. `g a`.
+    #[serde(default)]
+    pub hotkey: Option<String>,
     pub tabs: usize,
     pub panes: usize,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub tab_list: Vec<FavTabInfo>,
+    #[serde(default)]
+    pub connected_clients: usize,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// When the session was last switched to, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub last_used: Option<u64>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct FavTabInfo {
+    pub name: String,
+    pub panes: Vec<FavPaneInfo>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct FavPaneInfo {
+    pub title: String,
+    pub command: Option<String>,
+    #[serde(default)]
+    pub exited: bool,
+    #[serde(default)]
+    pub exit_status: Option<i32>,
+}
+
+impl From<&SessionInfo> for FavSessionInfo {
+    fn from(info: &SessionInfo) -> Self {
+        let mut tabs = info.tabs.clone();
+        tabs.sort_by_key(|tab| tab.position);
+
+        let tab_list: Vec<FavTabInfo> = tabs
+            .iter()
+            .map(|tab| FavTabInfo {
+                name: tab.name.clone(),
+                panes: info
+                    .panes
+                    .panes
+                    .get(&tab.position)
+                    .map(|panes| {
+                        panes
+                            .iter()
+                            .filter(|pane| !pane.is_plugin)
+                            .map(|pane| FavPaneInfo {
+                                title: pane.title.clone(),
+                                command: pane.terminal_command.clone(),
+                                exited: pane.exited,
+                                exit_status: pane.exit_status,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        FavSessionInfo {
+            name: info.name.clone(),
+            is_active: true,
+            tabs: info.tabs.len(),
+            panes: info
+                .panes
+                .panes
+                .values()
+                .flat_map(|v| v.iter())
+                .filter(|pane| !(pane.is_plugin))
+                .count(),
+            tab_list,
+            connected_clients: info.connected_clients,
+            ..Default::default()
+        }
+    }
+}
+
+impl FavSessionInfo {
+    /// Carries the user metadata of a previously known entry over to a fresh session update.
+    /// Dead sessions keep their last known tabs and panes.
+    pub fn with_metadata(self, previous: &FavSessionInfo) -> Self {
+        FavSessionInfo {
+            assigned_number: previous.assigned_number,
+            hotkey: previous.hotkey.clone(),
+            tags: previous.tags.clone(),
+            notes: previous.notes.clone(),
+            last_used: previous.last_used,
+            tab_list: if self.is_active {
+                self.tab_list
+            } else {
+                previous.tab_list.clone()
+            },
+            ..self
+        }
+    }
+
+    /// The entry without the live state of a running session: its tabs, panes and clients
+    /// change all the time and aren't worth saving the cache for.
+    pub fn saved_state(&self) -> FavSessionInfo {
+        if !self.is_active {
+            return self.clone();
+        }
+        FavSessionInfo {
+            tabs: 0,
+            panes: 0,
+            tab_list: vec![],
+            connected_clients: 0,
+            ..self.clone()
+        }
+    }
+
+    /// Finds a tab name, pane title or command containing `text`, describing where it matched.
+    pub fn find_in_contents(&self, text: &str) -> Option<String> {
+        let text = text.to_lowercase();
+        for tab in self.tab_list.iter() {
+            if tab.name.to_lowercase().contains(&text) {
+                return Some(format!("tab {}", tab.name));
+            }
+            for pane in tab.panes.iter() {
+                if let Some(command) = &pane.command {
+                    if command.to_lowercase().contains(&text) {
+                        return Some(format!("{} > {}", tab.name, command));
+                    }
+                }
+                if pane.title.to_lowercase().contains(&text) {
+                    return Some(format!("{} > {}", tab.name, pane.title));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum FavsDisplay {
+    #[default]
+    Manager,
+    Bar,
+}
+
+impl From<&String> for FavsDisplay {
+    fn from(value: &String) -> Self {
+        match value.trim() {
+            "bar" => FavsDisplay::Bar,
+            _ => FavsDisplay::Manager,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Copy, Debug)]
 pub enum FavsCommandType {
     ReadCache,
+    ReadBackup,
     WriteCache,
+    ReadNoColor,
+    Export,
 }
 
 impl Display for FavsCommandType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FavsCommandType::ReadCache => write!(f, "ReadCache"),
+            FavsCommandType::ReadBackup => write!(f, "ReadBackup"),
             FavsCommandType::WriteCache => write!(f, "WriteCache"),
+            FavsCommandType::ReadNoColor => write!(f, "ReadNoColor"),
+            FavsCommandType::Export => write!(f, "Export"),
         }
     }
 }
@@ -37,7 +276,10 @@ impl From<&String> for FavsCommandType {
     fn from(value: &String) -> Self {
         match value.as_str() {
             "ReadCache" => FavsCommandType::ReadCache,
+            "ReadBackup" => FavsCommandType::ReadBackup,
             "WriteCache" => FavsCommandType::WriteCache,
+            "ReadNoColor" => FavsCommandType::ReadNoColor,
+            "Export" => FavsCommandType::Export,
             _ => FavsCommandType::ReadCache,
         }
     }
@@ -48,3 +290,46 @@ impl FavsCommandType {
         "command_type".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numbers_in_every_bank() {
+        assert_eq!(parse_number("3"), Some(3));
+        assert_eq!(parse_number(" 0 "), Some(0));
+        assert_eq!(parse_number("alt-3"), Some(13));
+        assert_eq!(parse_number("Ctrl-3"), Some(23));
+        assert_eq!(parse_number("CTRL-ALT-9"), Some(39));
+        for invalid in ["", "10", "-3", "alt-", "alt-10", "shift-3", "alt3", "a"] {
+            assert_eq!(parse_number(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn labels_numbers_by_bank() {
+        assert_eq!(number_label(3), "3");
+        assert_eq!(number_label(13), "Alt-3");
+        assert_eq!(number_label(20), "Ctrl-0");
+        assert_eq!(number_label(39), "Ctrl-Alt-9");
+        for number in 0..BANK_SIZE * BANKS.len() as u8 {
+            assert_eq!(parse_number(&number_label(number)), Some(number));
+        }
+    }
+
+    #[test]
+    fn reads_numbers_from_digit_keys() {
+        let digit = |c| KeyWithModifier::new(BareKey::Char(c));
+        assert_eq!(key_number(&digit('3')), Some(3));
+        assert_eq!(key_number(&digit('3').with_alt_modifier()), Some(13));
+        assert_eq!(key_number(&digit('0').with_ctrl_modifier()), Some(20));
+        assert_eq!(
+            key_number(&digit('9').with_ctrl_modifier().with_alt_modifier()),
+            Some(39)
+        );
+        assert_eq!(key_number(&digit('3').with_shift_modifier()), None);
+        assert_eq!(key_number(&digit('a')), None);
+        assert_eq!(key_number(&KeyWithModifier::new(BareKey::F(3))), None);
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use zellij_tile::prelude::Mouse;
+
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    reload::{defer, Deferred, SwitchTarget},
+};
+
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+pub fn match_navigation_mouse(ctx: &mut Favs, mouse: &Mouse) -> bool {
+    if !matches!(ctx.mode, FavMode::NavigateFavs | FavMode::NavigateFlush) {
+        return false;
+    }
+    match mouse {
+        Mouse::ScrollUp(lines) => ctx.move_cursor(-(*lines as isize)),
+        Mouse::ScrollDown(lines) => ctx.move_cursor(*lines as isize),
+        Mouse::LeftClick(line, col) => {
+            let Ok(line) = usize::try_from(*line) else {
+                return false;
+            };
+            let layout = ctx.get_layout();
+            let Some((column, rect)) = [FavMode::NavigateFavs, FavMode::NavigateFlush]
+                .into_iter()
+                .map(|column| (column.clone(), layout.column(&column)))
+                .find(|(_, rect)| rect.contains(*col, line))
+            else {
+                return false;
+            };
+
+            if line == rect.y {
+                ctx.mode = column;
+                ctx.last_click = None;
+                return true;
+            }
+
+            let idx = line - rect.y - 1 + ctx.column_cursor(&column).offset;
+            let (fav_sessions, flush_sessions) = ctx.get_filtered_sessions();
+            let sessions = if column == FavMode::NavigateFavs {
+                fav_sessions
+            } else {
+                flush_sessions
+            };
+            let Some(session) = sessions.get(idx) else {
+                return false;
+            };
+
+            let is_double_click = ctx.last_click.as_ref().is_some_and(|(time, name)| {
+                name == &session.name && time.elapsed() < DOUBLE_CLICK_INTERVAL
+            });
+            if is_double_click {
+                ctx.last_click = None;
+                defer(
+                    ctx,
+                    Deferred::Switch(SwitchTarget::Session(session.name.clone())),
+                );
+                return false;
+            }
+
+            ctx.last_click = Some((Instant::now(), session.name.clone()));
+            ctx.column_cursor_mut(&column).index = idx;
+            ctx.mode = column;
+        }
+        _ => return false,
+    }
+    true
+}
@@ -1,156 +1,382 @@
+use serde::{Deserialize, Serialize};
 use zellij_tile::{
-    prelude::BareKey,
-    shim::{close_focus, close_self, delete_dead_session, kill_sessions, switch_session},
+    prelude::{BareKey, KeyWithModifier},
+    shim::{close_focus, kill_sessions},
 };
 
-use crate::{favs::Favs, favs_mode::FavMode};
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    help::open_help,
+    jump::open_jump,
+    key_number,
+    keymap::{Action, KeyMatch},
+    palette::open_palette,
+    reload::{defer, Deferred, SwitchTarget},
+    renames::open_rename_questions,
+    status::Severity,
+    text_input::{typed_char, TextInput},
+};
 
-pub fn match_navigation_keys(ctx: &mut Favs, key: &BareKey) -> bool {
-    let (fav_sessions, flush_sessions) = ctx.get_filtered_sessions();
+/// Cursor and scroll offset of a column, kept while the other column is focused.
+#[derive(Default, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ColumnCursor {
+    pub index: usize,
+    pub offset: usize,
+}
 
-    let arr_length = if ctx.mode == FavMode::NavigateFavs {
-        fav_sessions.len()
-    } else {
-        flush_sessions.len()
-    };
-    match key {
-        BareKey::Char('h') | BareKey::Left => {
-            ctx.mode = FavMode::NavigateFavs;
-            ctx.cursor = if ctx.cursor < fav_sessions.len() {
-                ctx.cursor
-            } else {
-                fav_sessions.len().saturating_sub(1)
-            };
+impl ColumnCursor {
+    /// Keeps the cursor within `len` sessions and scrolls just enough to keep it inside a column
+    /// showing `height` of them.
+    pub fn scroll(&mut self, len: usize, height: usize) {
+        self.index = self.index.min(len.saturating_sub(1));
+        if self.index < self.offset {
+            self.offset = self.index;
+        } else if height > 0 && self.index >= self.offset + height {
+            self.offset = self.index + 1 - height;
         }
-        BareKey::Char('j') | BareKey::Down => {
-            if ctx.cursor + 1 < arr_length {
-                ctx.cursor += 1;
-            }
+        self.offset = self.offset.min(len.saturating_sub(height));
+    }
+}
+
+pub fn match_navigation_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    if ctx.tag_input.is_some() {
+        return match_tag_input(ctx, key);
+    }
+    if ctx.rename_input.is_some() {
+        return match_rename_input(ctx, key);
+    }
+    let action = match ctx.resolve_key(key) {
+        KeyMatch::Action(action) => action,
+        KeyMatch::Pending => return false,
+        KeyMatch::Hotkey(name) => {
+            defer(ctx, Deferred::Switch(SwitchTarget::Session(name)));
+            return true;
         }
-        BareKey::Char('k') | BareKey::Up => {
-            if ctx.cursor > 0 {
-                ctx.cursor -= 1;
-            }
+        KeyMatch::None => return match_quick_access(ctx, key),
+    };
+    let (_, flush_sessions) = ctx.get_filtered_sessions();
+
+    match action {
+        Action::FocusFavs => {
+            ctx.mode = FavMode::NavigateFavs;
         }
-        BareKey::Char('l') | BareKey::Right => {
+        Action::MoveDown => ctx.move_cursor(1),
+        Action::MoveUp => ctx.move_cursor(-1),
+        Action::FocusFlush => {
             ctx.mode = FavMode::NavigateFlush;
-            ctx.cursor = if ctx.cursor < flush_sessions.len() {
-                ctx.cursor
-            } else {
-                flush_sessions.len().saturating_sub(1)
-            };
         }
-        BareKey::Char('f') => {
-            let sessions_to_delete: Vec<String> = flush_sessions
-                .iter()
-                .filter(|session| session.is_active)
-                .map(|session| session.name.clone())
-                .collect();
-
-            kill_sessions(&sessions_to_delete);
-
-            for session in flush_sessions.iter() {
-                delete_dead_session(&session.name);
+        Action::PageDown => ctx.move_cursor(ctx.page_size() as isize),
+        Action::PageUp => ctx.move_cursor(-(ctx.page_size() as isize)),
+        Action::First => ctx.move_cursor(isize::MIN),
+        Action::Last => ctx.move_cursor(isize::MAX),
+        Action::Flush => {
+            if !flush_sessions.is_empty() {
+                ctx.push_undo_with_note(
+                    format!("flush of {} sessions", flush_sessions.len()),
+                    "deleted sessions can't be brought back",
+                );
             }
-
-            ctx.flush_sessions
-                .retain(|session| flush_sessions.iter().any(|s| s.name == session.name));
+            let summary = ctx.delete_sessions(&flush_sessions);
+            ctx.notify(Severity::Success, summary);
             ctx.commit_fav_changes();
         }
-        BareKey::Char('a') => {
+        Action::Assign => {
             ctx.current_column = Some(ctx.mode.clone());
             ctx.mode = FavMode::AssignNumber;
         }
-        BareKey::Char('/') => {
+        Action::Filter => {
             ctx.mode = FavMode::Filter;
-            ctx.filter = Some(String::new());
+            // Reopening the prompt edits the filter in place rather than starting over.
+            let value = ctx
+                .filter
+                .as_ref()
+                .map(|filter| filter.value.clone())
+                .unwrap_or_default();
+            ctx.filter = Some(TextInput::new(&value));
+        }
+        Action::Presets => {
+            ctx.mode = FavMode::Presets;
+            ctx.preset_cursor = 0;
         }
-        BareKey::Char(' ') => {
-            if ctx.mode == FavMode::NavigateFavs {
-                if fav_sessions.is_empty() {
-                    return false;
+        Action::MoveSession => {
+            let sessions = ctx.target_sessions();
+            if sessions.is_empty() {
+                return false;
+            }
+            let (target, description) = if ctx.mode == FavMode::NavigateFavs {
+                (FavMode::NavigateFlush, "Flush")
+            } else {
+                (FavMode::NavigateFavs, "Favorites")
+            };
+            ctx.push_undo(format!("move to {}", description));
+            for session in sessions.iter() {
+                if target == FavMode::NavigateFlush {
+                    ctx.move_to_flush(&session.name);
+                } else {
+                    ctx.move_to_favs(&session.name);
                 }
-
-                let session = fav_sessions[ctx.cursor].clone();
-                let session_idx = ctx
-                    .fav_sessions
-                    .iter()
-                    .position(|s| s.name == session.name)
-                    .unwrap();
-
-                ctx.fav_sessions.remove(session_idx);
-                ctx.flush_sessions.push(session);
-                if ctx.cursor == ctx.fav_sessions.len() && !ctx.fav_sessions.is_empty() {
-                    ctx.cursor -= 1;
+            }
+            if !ctx.marked.is_empty() {
+                ctx.notify(
+                    Severity::Success,
+                    format!("moved {} sessions to {}", sessions.len(), description),
+                );
+                ctx.marked.clear();
+            }
+            ctx.commit_fav_changes();
+        }
+        Action::ToggleMark => {
+            let Some(session) = ctx.get_selected_session() else {
+                return false;
+            };
+            if !ctx.marked.remove(&session.name) {
+                ctx.marked.insert(session.name);
+            }
+            ctx.move_cursor(1);
+        }
+        Action::MarkAll => {
+            let visible = ctx.visible_sessions();
+            if visible
+                .iter()
+                .all(|session| ctx.marked.contains(&session.name))
+            {
+                for session in visible {
+                    ctx.marked.remove(&session.name);
                 }
             } else {
-                if flush_sessions.is_empty() {
-                    return false;
-                }
-
-                let session = flush_sessions[ctx.cursor].clone();
-                let session_idx = ctx
-                    .flush_sessions
-                    .iter()
-                    .position(|s| s.name == session.name)
-                    .unwrap();
-
-                ctx.flush_sessions.remove(session_idx);
-                ctx.fav_sessions.push(session);
-                if ctx.cursor == ctx.flush_sessions.len() && !ctx.flush_sessions.is_empty() {
-                    ctx.cursor -= 1;
+                ctx.marked
+                    .extend(visible.into_iter().map(|session| session.name));
+            }
+        }
+        Action::InvertMarks => {
+            for session in ctx.visible_sessions() {
+                if !ctx.marked.remove(&session.name) {
+                    ctx.marked.insert(session.name);
                 }
             }
+        }
+        Action::Kill => {
+            let sessions: Vec<String> = ctx
+                .target_sessions()
+                .into_iter()
+                .filter(|session| session.is_active)
+                .map(|session| session.name)
+                .collect();
+            if sessions.is_empty() {
+                ctx.notify(Severity::Warning, "no running sessions to kill");
+                return true;
+            }
+            // Killing leaves the lists as they are, so there's nothing to undo.
+            kill_sessions(&sessions);
+            ctx.marked.clear();
+            ctx.notify(
+                Severity::Success,
+                format!("killed {} sessions", sessions.len()),
+            );
+        }
+        Action::Delete => {
+            let sessions = ctx.target_sessions();
+            if sessions.is_empty() {
+                return false;
+            }
+            ctx.push_undo_with_note(
+                format!("delete of {} sessions", sessions.len()),
+                "deleted sessions can't be brought back",
+            );
+            let summary = ctx.delete_sessions(&sessions);
+            ctx.marked.clear();
+            ctx.notify(Severity::Success, summary);
             ctx.commit_fav_changes();
         }
-        BareKey::Tab => {
-            if ctx.mode == FavMode::NavigateFavs {
-                ctx.mode = FavMode::NavigateFlush;
-                ctx.cursor = ctx.cursor.min(ctx.flush_sessions.len());
-            } else {
-                ctx.mode = FavMode::NavigateFavs;
-                ctx.cursor = ctx.cursor.min(ctx.fav_sessions.len());
+        Action::Tag => {
+            if ctx.target_sessions().is_empty() {
+                return false;
             }
-            ctx.cursor = 0;
+            ctx.tag_input = Some(TextInput::default());
         }
-        BareKey::Enter => {
-            close_focus();
-            let session = if ctx.mode == FavMode::NavigateFavs {
-                fav_sessions[ctx.cursor].clone()
-            } else {
-                flush_sessions[ctx.cursor].clone()
+        Action::Export => {
+            let sessions = ctx.target_sessions();
+            if sessions.is_empty() {
+                return false;
+            }
+            ctx.export_sessions(&sessions, &ctx.export_path);
+        }
+        Action::Rename => {
+            let Some(session) = ctx.get_selected_session() else {
+                return false;
             };
-            switch_session(Some(session.name.as_str()));
-            close_focus();
+            match ctx.rename_blocker(&session.name) {
+                Some(reason) => ctx.notify(Severity::Warning, reason),
+                None => ctx.rename_input = Some(TextInput::new(&session.name)),
+            }
         }
-        BareKey::Esc | BareKey::Char('q') => {
-            close_self();
+        Action::Undo => match ctx.undo() {
+            Ok(message) => ctx.notify(Severity::Success, message),
+            Err(e) => ctx.notify(Severity::Warning, e),
+        },
+        Action::SwitchColumn => {
+            ctx.mode = if ctx.mode == FavMode::NavigateFavs {
+                FavMode::NavigateFlush
+            } else {
+                FavMode::NavigateFavs
+            };
         }
-        BareKey::Char('?') => {
-            ctx.mode = FavMode::Help;
+        Action::Open => {
+            let Some(session) = ctx.get_selected_session() else {
+                return false;
+            };
+            close_focus();
+            defer(ctx, Deferred::Switch(SwitchTarget::Session(session.name)));
         }
-        BareKey::Char('t') => ctx.display_tab_panes = !ctx.display_tab_panes,
+        Action::Back => defer(ctx, Deferred::Switch(SwitchTarget::Back)),
+        Action::Next => defer(ctx, Deferred::Switch(SwitchTarget::Cycle(true))),
+        Action::Prev => defer(ctx, Deferred::Switch(SwitchTarget::Cycle(false))),
+        Action::Close => defer(ctx, Deferred::Close),
+        Action::Help => open_help(ctx),
+        Action::Command => open_palette(ctx),
+        Action::Jump => open_jump(ctx),
+        Action::Renames => open_rename_questions(ctx),
+        Action::ToggleCounters => ctx.display_tab_panes = !ctx.display_tab_panes,
+        Action::ToggleDetails => ctx.show_details = !ctx.show_details,
+        _ => return false,
+    };
+    true
+}
 
-        BareKey::Char(c) if c.is_ascii_digit() => {
-            let digit = c.to_digit(10).unwrap() as u8;
-            for session in ctx.fav_sessions.iter() {
-                if let Some(assigned) = session.assigned_number {
-                    if assigned == digit {
-                        switch_session(Some(session.name.as_str()));
-                        close_focus();
-                    }
-                }
+/// Switches to the session with the quick access number typed, if any.
+fn match_quick_access(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    let Some(number) = key_number(key) else {
+        return false;
+    };
+    let session = ctx
+        .fav_sessions
+        .iter()
+        .chain(ctx.flush_sessions.iter())
+        .find(|session| session.assigned_number == Some(number))
+        .cloned();
+    if let Some(session) = session {
+        defer(ctx, Deferred::Switch(SwitchTarget::Session(session.name)));
+    }
+    true
+}
+
+/// Typing the tag to add to, or remove from, the marked sessions.
+fn match_tag_input(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    let Some(tag_input) = ctx.tag_input.as_mut() else {
+        return false;
+    };
+    match (typed_char(key), key.bare_key) {
+        (Some(c), _) => tag_input.insert(&c.to_string()),
+        (_, BareKey::Backspace) => tag_input.backspace(),
+        (_, BareKey::Left) => tag_input.left(),
+        (_, BareKey::Right) => tag_input.right(),
+        (_, BareKey::Esc) => ctx.tag_input = None,
+        (_, BareKey::Enter) => {
+            let tag = tag_input.value.trim().trim_start_matches('#').to_string();
+            ctx.tag_input = None;
+            if tag.is_empty() {
+                return true;
             }
-            for session in ctx.flush_sessions.iter() {
-                if let Some(assigned) = session.assigned_number {
-                    if assigned == digit {
-                        switch_session(Some(session.name.as_str()));
-                        close_focus();
-                    }
-                }
+            let sessions = ctx.target_sessions();
+            let enabled = !sessions.iter().all(|session| session.tags.contains(&tag));
+            ctx.push_undo(format!("tag #{}", tag));
+            for session in sessions.iter() {
+                ctx.set_tag(&session.name, &tag, enabled);
             }
+            let message = if enabled {
+                format!("tagged {} sessions with #{}", sessions.len(), tag)
+            } else {
+                format!("removed #{} from {} sessions", tag, sessions.len())
+            };
+            ctx.notify(Severity::Success, message);
+            ctx.marked.clear();
+            ctx.commit_fav_changes();
         }
         _ => return false,
+    }
+    true
+}
+
+/// Typing the new name of the highlighted session.
+fn match_rename_input(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    let Some(rename_input) = ctx.rename_input.as_mut() else {
+        return false;
     };
+    match (typed_char(key), key.bare_key) {
+        (Some(c), _) => rename_input.insert(&c.to_string()),
+        (_, BareKey::Backspace) => rename_input.backspace(),
+        (_, BareKey::Delete) => rename_input.delete(),
+        (_, BareKey::Left) => rename_input.left(),
+        (_, BareKey::Right) => rename_input.right(),
+        (_, BareKey::Home) => rename_input.home(),
+        (_, BareKey::End) => rename_input.end(),
+        (_, BareKey::Esc) => ctx.rename_input = None,
+        (_, BareKey::Enter) => {
+            let new_name = rename_input.value.trim().to_string();
+            ctx.rename_input = None;
+            let Some(session) = ctx.get_selected_session() else {
+                return true;
+            };
+            if new_name == session.name {
+                return true;
+            }
+            match ctx.rename_session(&session.name, &new_name) {
+                Ok(()) => ctx.notify(
+                    Severity::Success,
+                    format!("renamed {} to {}", session.name, new_name),
+                ),
+                Err(e) => ctx.notify(Severity::Error, e),
+            }
+        }
+        _ => return false,
+    }
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrolled(index: usize, offset: usize, len: usize, height: usize) -> ColumnCursor {
+        let mut cursor = ColumnCursor { index, offset };
+        cursor.scroll(len, height);
+        cursor
+    }
+
+    fn at(index: usize, offset: usize) -> ColumnCursor {
+        ColumnCursor { index, offset }
+    }
+
+    #[test]
+    fn scrolls_just_enough_to_show_the_cursor() {
+        assert_eq!(scrolled(0, 0, 10, 3), at(0, 0));
+        assert_eq!(scrolled(3, 0, 10, 3), at(3, 1));
+        assert_eq!(scrolled(2, 5, 10, 3), at(2, 2));
+        assert_eq!(scrolled(6, 5, 10, 3), at(6, 5));
+    }
+
+    #[test]
+    fn clamps_a_cursor_past_the_end() {
+        assert_eq!(scrolled(10, 0, 3, 2), at(2, 1));
+        assert_eq!(scrolled(10, 8, 3, 5), at(2, 0));
+        assert_eq!(scrolled(4, 2, 0, 3), at(0, 0));
+    }
+
+    #[test]
+    fn shows_more_sessions_when_the_list_shrinks() {
+        // Scrolled to the bottom of 10 sessions, then filtered down to 6.
+        assert_eq!(scrolled(9, 7, 6, 3), at(5, 3));
+        assert_eq!(scrolled(4, 3, 2, 3), at(1, 0));
+        // The column grew taller than what's left below the offset.
+        assert_eq!(scrolled(8, 6, 10, 8), at(8, 2));
+    }
+
+    #[test]
+    fn keeps_the_cursor_without_rows() {
+        assert_eq!(scrolled(5, 0, 10, 0), at(5, 0));
+        assert_eq!(scrolled(5, 7, 10, 0), at(5, 5));
+        assert_eq!(scrolled(12, 3, 10, 0), at(9, 3));
+        assert_eq!(scrolled(0, 0, 0, 0), at(0, 0));
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::BTreeSet;
+
+use zellij_tile::prelude::KeyWithModifier;
+
+use crate::{
+    commands::COMMAND_NAMES,
+    favs::Favs,
+    favs_mode::FavMode,
+    help::open_help,
+    keymap::{Action, KeyMatch},
+    reload::{defer, Deferred},
+    sort::SortMode,
+    text_input::{typed_char, TextInput},
+    theme::{Line, Role},
+};
+
+pub fn open_palette(ctx: &mut Favs) {
+    ctx.current_column = Some(ctx.mode.clone());
+    ctx.mode = FavMode::Command;
+    ctx.command_input = Some(TextInput::default());
+    ctx.completions.clear();
+}
+
+fn close_palette(ctx: &mut Favs) {
+    ctx.command_input = None;
+    ctx.completions.clear();
+    ctx.mode = ctx.current_column.take().unwrap_or(FavMode::NavigateFavs);
+}
+
+/// Runs the typed command through the same parser as `zellij pipe`, acting on the marked or
+/// highlighted sessions when the command leaves its session out. Like pipe commands, it runs
+/// once the cache has been read again.
+fn run_palette(ctx: &mut Favs) {
+    let input = ctx
+        .command_input
+        .as_ref()
+        .map(|input| input.value.trim().to_string())
+        .unwrap_or_default();
+    close_palette(ctx);
+    if input.is_empty() {
+        return;
+    }
+    let targets: Vec<String> = ctx
+        .target_sessions()
+        .into_iter()
+        .map(|session| session.name)
+        .collect();
+    defer(ctx, Deferred::Command(input, targets));
+}
+
+/// Completions for the last word of `input`: a command name, then the arguments it takes.
+fn candidates(ctx: &Favs, input: &str) -> Vec<String> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let (index, prefix) = match words.last() {
+        Some(word) if !input.ends_with(char::is_whitespace) => (words.len() - 1, *word),
+        _ => (words.len(), ""),
+    };
+    let session_names = || {
+        ctx.fav_sessions
+            .iter()
+            .chain(ctx.flush_sessions.iter())
+            .map(|session| session.name.clone())
+            .collect::<Vec<String>>()
+    };
+    let options: Vec<String> = match (index, words.first().copied()) {
+        (0, _) => COMMAND_NAMES.iter().map(|name| name.to_string()).collect(),
+        (1, Some("sort")) => SortMode::variants()
+            .iter()
+            .map(|mode| mode.to_string())
+            .collect(),
+        (1, Some("flush")) => vec!["--dry-run".to_string()],
+        (1, Some("list")) => vec!["--json".to_string()],
+        (1, Some("add" | "remove" | "assign" | "hotkey" | "note" | "rename")) => session_names(),
+        (1 | 2, Some("tag" | "untag")) => {
+            let tags: BTreeSet<String> = ctx
+                .fav_sessions
+                .iter()
+                .chain(ctx.flush_sessions.iter())
+                .flat_map(|session| session.tags.iter().cloned())
+                .collect();
+            let mut options = if index == 1 { session_names() } else { vec![] };
+            options.extend(tags);
+            options
+        }
+        _ => vec![],
+    };
+    options
+        .into_iter()
+        .filter(|option| option.starts_with(prefix))
+        .collect()
+}
+
+fn common_prefix(words: &[String]) -> String {
+    let Some(first) = words.first() else {
+        return String::new();
+    };
+    first
+        .chars()
+        .enumerate()
+        .take_while(|(i, c)| words.iter().all(|word| word.chars().nth(*i) == Some(*c)))
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// Completes the last word, or as much of it as all candidates share, listing them when there
+/// are several.
+fn complete(ctx: &mut Favs) {
+    let Some(input) = ctx.command_input.as_ref() else {
+        return;
+    };
+    let value = input.value.clone();
+    let candidates = candidates(ctx, &value);
+    let completion = match candidates.as_slice() {
+        [] => return,
+        [candidate] => format!("{} ", candidate),
+        candidates => common_prefix(candidates),
+    };
+    let word_start = value.trim_end_matches(|c: char| !c.is_whitespace()).len();
+    ctx.command_input = Some(TextInput::new(&format!(
+        "{}{}",
+        &value[..word_start],
+        completion
+    )));
+    ctx.completions = if candidates.len() > 1 {
+        candidates
+    } else {
+        vec![]
+    };
+}
+
+pub fn match_palette_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    let action = match ctx.resolve_key(key) {
+        KeyMatch::Action(action) => Some(action),
+        KeyMatch::Pending => return false,
+        KeyMatch::Hotkey(_) | KeyMatch::None => None,
+    };
+    let Some(input) = ctx.command_input.as_mut() else {
+        close_palette(ctx);
+        return true;
+    };
+
+    let Some(action) = action else {
+        let Some(c) = typed_char(key) else {
+            return false;
+        };
+        input.insert(&c.to_string());
+        ctx.completions.clear();
+        return true;
+    };
+
+    match action {
+        Action::DeleteWord => input.delete_word(),
+        Action::ClearInput => input.clear(),
+        Action::CursorStart => input.home(),
+        Action::CursorEnd => input.end(),
+        Action::CursorLeft => input.left(),
+        Action::CursorRight => input.right(),
+        Action::DeleteBack => input.backspace(),
+        Action::DeleteForward => input.delete(),
+        Action::Complete => {
+            complete(ctx);
+            return true;
+        }
+        Action::Confirm => {
+            run_palette(ctx);
+            return true;
+        }
+        Action::Cancel => {
+            close_palette(ctx);
+            return true;
+        }
+        Action::InputHelp => {
+            open_help(ctx);
+            return true;
+        }
+        _ => return false,
+    }
+    ctx.completions.clear();
+    true
+}
+
+pub fn match_palette_paste(ctx: &mut Favs, text: &str) -> bool {
+    if ctx.mode != FavMode::Command {
+        return false;
+    }
+    if let Some(input) = ctx.command_input.as_mut() {
+        input.insert(&text.replace(['\n', '\r'], " "));
+    }
+    true
+}
+
+pub fn palette_line(ctx: &Favs) -> Line {
+    let mut line = Line::new().styled(":", Role::Prompt);
+    if let Some(input) = &ctx.command_input {
+        let (before, current, after) = input.split_at_cursor();
+        line = line
+            .plain(before)
+            .styled(current.unwrap_or('_'), Role::Prompt)
+            .plain(after);
+    }
+    line
+}
+
+/// Completions while there are several to pick from, otherwise the status or a reminder of
+/// the palette keys.
+pub fn palette_footer(ctx: &Favs) -> Line {
+    if !ctx.completions.is_empty() {
+        return Line::new().plain(ctx.completions.join("  "));
+    }
+    if let Some(status) = &ctx.status {
+        return status.line();
+    }
+    Line::new().plain(format!(
+        "{} complete  {} run  {} close",
+        ctx.keymap.label(&Action::Complete),
+        ctx.keymap.label(&Action::Confirm),
+        ctx.keymap.label(&Action::Cancel),
+    ))
+}
@@ -0,0 +1,39 @@
+use zellij_tile::{
+    prelude::{PipeMessage, PipeSource},
+    shim::{block_cli_pipe_input, cli_pipe_output, unblock_cli_pipe_input},
+};
+
+use crate::{
+    commands::FavsCommand,
+    favs::Favs,
+    reload::{defer, Deferred},
+};
+
+/// Queues the command until the cache has been read again, so it doesn't act on, and save, what
+/// this instance read when it loaded.
+pub fn match_pipe_message(ctx: &mut Favs, pipe_message: PipeMessage) -> bool {
+    if let PipeSource::Cli(_) = pipe_message.source {
+        block_cli_pipe_input(&pipe_message.name);
+    }
+    defer(ctx, Deferred::Pipe(pipe_message));
+    false
+}
+
+pub fn run_pipe_message(ctx: &mut Favs, pipe_message: PipeMessage) {
+    let input = match &pipe_message.payload {
+        Some(payload) if !payload.trim().is_empty() => payload.clone(),
+        _ => pipe_message.name.clone(),
+    };
+
+    let targets: Vec<String> = ctx.current_session.iter().cloned().collect();
+    let output = FavsCommand::parse(&input).and_then(|command| command.run(ctx, &targets));
+
+    if let PipeSource::Cli(_) = pipe_message.source {
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => format!("error: {}", e),
+        };
+        cli_pipe_output(&pipe_message.name, &format!("{}\n", output.trim_end()));
+        unblock_cli_pipe_input(&pipe_message.name);
+    }
+}
@@ -0,0 +1,124 @@
+use zellij_tile::prelude::{BareKey, KeyWithModifier};
+
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    help::open_help,
+    keymap::{Action, KeyMatch},
+    navigate::ColumnCursor,
+    text_input::{typed_char, TextInput},
+    theme::{Line, Role},
+};
+
+pub fn match_preset_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    if let Some(name) = ctx.preset_name.as_mut() {
+        match (typed_char(key), key.bare_key) {
+            (Some(c), _) => name.insert(&c.to_string()),
+            (_, BareKey::Backspace) => name.backspace(),
+            (_, BareKey::Left) => name.left(),
+            (_, BareKey::Right) => name.right(),
+            (_, BareKey::Enter) => {
+                let name = name.value.trim().to_string();
+                let query = ctx.filter.as_ref().map(|f| f.value.clone());
+                if let (false, Some(query)) = (name.is_empty(), query) {
+                    ctx.presets.insert(name, query);
+                    ctx.commit_fav_changes();
+                }
+                ctx.preset_name = None;
+            }
+            (_, BareKey::Esc) => ctx.preset_name = None,
+            _ => return false,
+        }
+        return true;
+    }
+
+    let KeyMatch::Action(action) = ctx.resolve_key(key) else {
+        return false;
+    };
+    let presets = ctx.get_presets();
+    match action {
+        Action::MoveDown => {
+            if ctx.preset_cursor + 1 < presets.len() {
+                ctx.preset_cursor += 1;
+            }
+        }
+        Action::MoveUp => {
+            ctx.preset_cursor = ctx.preset_cursor.saturating_sub(1);
+        }
+        Action::Confirm => {
+            if let Some((_, query)) = presets.get(ctx.preset_cursor) {
+                ctx.filter = Some(TextInput::new(query));
+                ctx.push_filter_history();
+                ctx.mode = FavMode::NavigateFavs;
+                ctx.fav_cursor = ColumnCursor::default();
+                ctx.flush_cursor = ColumnCursor::default();
+            }
+        }
+        Action::SavePreset => {
+            if ctx.filter.as_ref().is_some_and(|f| !f.value.is_empty()) {
+                ctx.preset_name = Some(TextInput::default());
+            }
+        }
+        Action::DeletePreset => {
+            if let Some((name, _)) = presets.get(ctx.preset_cursor) {
+                if ctx.presets.remove(name).is_some() {
+                    ctx.commit_fav_changes();
+                    ctx.preset_cursor = ctx.preset_cursor.saturating_sub(1);
+                }
+            }
+        }
+        Action::Help => open_help(ctx),
+        Action::Close => {
+            ctx.mode = FavMode::NavigateFavs;
+        }
+        _ => return false,
+    }
+    true
+}
+
+pub fn render_presets(ctx: &Favs, rows: usize) {
+    ctx.theme.print(
+        &Line::new().styled("Filter presets", Role::Title),
+        0,
+        0,
+        Some(ctx.cols),
+    );
+
+    let presets = ctx.get_presets();
+    if presets.is_empty() {
+        ctx.theme
+            .print(&Line::new().plain("No presets yet"), 0, 1, Some(ctx.cols));
+    }
+    let presets_space = rows.saturating_sub(3);
+    for (i, (name, query)) in presets.iter().enumerate().take(presets_space) {
+        let mut line = Line::new();
+        if i == ctx.preset_cursor {
+            line = line.plain("> ");
+        }
+        line = line
+            .styled(name, Role::Section)
+            .plain(format!(" {}", query));
+        if !ctx.presets.contains_key(name) {
+            line = line.plain(" (config)");
+        }
+        if i == ctx.preset_cursor {
+            line = line.selected();
+        }
+        ctx.theme.print(&line, 0, 1 + i, Some(ctx.cols));
+    }
+
+    if let Some(name) = &ctx.preset_name {
+        let (before, current, after) = name.split_at_cursor();
+        let line = Line::new()
+            .styled("Save current filter as: ", Role::Prompt)
+            .plain(before)
+            .styled(current.unwrap_or('_'), Role::Prompt)
+            .plain(after);
+        ctx.theme.print(
+            &line,
+            0,
+            (presets.len() + 1).min(rows.saturating_sub(1)),
+            Some(ctx.cols),
+        );
+    }
+}
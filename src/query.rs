@@ -0,0 +1,438 @@
+use crate::{fuzzy::fuzzy_match, parse_number, FavSessionInfo};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn compare(self, left: usize, right: usize) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Greater => left > right,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Counter {
+    Tabs,
+    Panes,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Query {
+    Text(String),
+    Active(bool),
+    Number(Option<u8>),
+    Count(Counter, Comparison, usize),
+    Tag(String),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Word(String),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut word = String::new();
+    let push_word = |word: &mut String, tokens: &mut Vec<Token>| {
+        if word.is_empty() {
+            return;
+        }
+        let token = match word.to_lowercase().as_str() {
+            "and" | "&" => Token::And,
+            "or" => Token::Or,
+            _ => Token::Word(word.clone()),
+        };
+        tokens.push(token);
+        word.clear();
+    };
+
+    for c in input.chars() {
+        match c {
+            c if c.is_whitespace() => push_word(&mut word, &mut tokens),
+            '(' | ')' | '|' => {
+                push_word(&mut word, &mut tokens);
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Or,
+                });
+            }
+            '-' if word.is_empty() => tokens.push(Token::Not),
+            c => word.push(c),
+        }
+    }
+    push_word(&mut word, &mut tokens);
+    tokens
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "" | "true" | "yes" | "y" | "1" => Ok(true),
+        "false" | "no" | "n" | "0" => Ok(false),
+        _ => Err(format!("expected yes or no, found '{}'", value)),
+    }
+}
+
+fn parse_term(word: &str) -> Result<Query, String> {
+    if let Some(tag) = word.strip_prefix('#') {
+        if tag.is_empty() {
+            return Err("missing tag name after '#'".to_string());
+        }
+        return Ok(Query::Tag(tag.to_lowercase()));
+    }
+
+    for (name, counter) in [("tabs", Counter::Tabs), ("panes", Counter::Panes)] {
+        let Some(rest) = word.strip_prefix(name) else {
+            continue;
+        };
+        let operators = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+            ("=", Comparison::Equal),
+            (":", Comparison::Equal),
+        ];
+        for (operator, comparison) in operators {
+            if let Some(value) = rest.strip_prefix(operator) {
+                let value = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("expected a number after '{}{}'", name, operator))?;
+                return Ok(Query::Count(counter, comparison, value));
+            }
+        }
+    }
+
+    if let Some((qualifier, value)) = word.split_once(':') {
+        return match qualifier.to_lowercase().as_str() {
+            "active" => Ok(Query::Active(parse_bool(value)?)),
+            "dead" => Ok(Query::Active(!parse_bool(value)?)),
+            "num" if value.is_empty() => Ok(Query::Number(None)),
+            "num" => match parse_number(value) {
+                Some(number) => Ok(Query::Number(Some(number))),
+                None => Err(format!("invalid quick access number '{}'", value)),
+            },
+            _ => Err(format!("unknown qualifier '{}:'", qualifier)),
+        };
+    }
+
+    Ok(Query::Text(word.to_string()))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+    fn parse_or(&mut self) -> Result<Query, String> {
+        let mut queries = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            queries.push(self.parse_and()?);
+        }
+        Ok(if queries.len() == 1 {
+            queries.remove(0)
+        } else {
+            Query::Or(queries)
+        })
+    }
+    fn parse_and(&mut self) -> Result<Query, String> {
+        let mut queries = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                    queries.push(self.parse_unary()?);
+                }
+                Some(Token::Word(_) | Token::Not | Token::Open) => {
+                    queries.push(self.parse_unary()?);
+                }
+                _ => break,
+            }
+        }
+        Ok(if queries.len() == 1 {
+            queries.remove(0)
+        } else {
+            Query::And(queries)
+        })
+    }
+    fn parse_unary(&mut self) -> Result<Query, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Query::Not(Box::new(self.parse_unary()?))),
+            Some(Token::Open) => {
+                let query = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(query),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Word(word)) => parse_term(&word),
+            Some(Token::Close) => Err("unexpected ')'".to_string()),
+            Some(Token::And) | Some(Token::Or) => {
+                Err("expected a term before operator".to_string())
+            }
+            None => Err("unexpected end of query".to_string()),
+        }
+    }
+}
+
+impl Query {
+    /// Parses a filter query. An empty or blank input yields `None`, meaning no filtering.
+    pub fn parse(input: &str) -> Result<Option<Query>, String> {
+        let tokens = tokenize(input);
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let query = parser.parse_or()?;
+        if parser.position < parser.tokens.len() {
+            return Err("unexpected ')'".to_string());
+        }
+        Ok(Some(query))
+    }
+
+    /// Returns the ranking score of a session, or `None` when it doesn't match.
+    /// With `search_contents`, text terms also match tab names, pane titles and commands.
+    pub fn score(&self, session: &FavSessionInfo, search_contents: bool) -> Option<i64> {
+        match self {
+            Query::Text(text) => fuzzy_match(text, &session.name)
+                .map(|m| m.score)
+                .or_else(|| {
+                    (search_contents && session.find_in_contents(text).is_some()).then_some(0)
+                }),
+            Query::Active(is_active) => (session.is_active == *is_active).then_some(0),
+            Query::Number(None) => session.assigned_number.map(|_| 0),
+            Query::Number(number) => (session.assigned_number == *number).then_some(0),
+            Query::Count(counter, comparison, value) => {
+                let count = match counter {
+                    Counter::Tabs => session.tabs,
+                    Counter::Panes => session.panes,
+                };
+                comparison.compare(count, *value).then_some(0)
+            }
+            Query::Tag(tag) => session
+                .tags
+                .iter()
+                .any(|t| t.to_lowercase() == *tag)
+                .then_some(0),
+            Query::Not(query) => match query.score(session, search_contents) {
+                Some(_) => None,
+                None => Some(0),
+            },
+            Query::And(queries) => queries
+                .iter()
+                .map(|q| q.score(session, search_contents))
+                .sum(),
+            Query::Or(queries) => queries
+                .iter()
+                .filter_map(|q| q.score(session, search_contents))
+                .max(),
+        }
+    }
+
+    /// Char indices of `name` matched by the positive text terms of the query.
+    pub fn highlight(&self, name: &str) -> Vec<usize> {
+        match self {
+            Query::Text(text) => fuzzy_match(text, name)
+                .map(|m| m.indices)
+                .unwrap_or_default(),
+            Query::And(queries) | Query::Or(queries) => {
+                queries.iter().flat_map(|q| q.highlight(name)).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Describes the tab or pane matched by a text term that didn't match the session name.
+    pub fn content_match(&self, session: &FavSessionInfo) -> Option<String> {
+        match self {
+            Query::Text(text) if fuzzy_match(text, &session.name).is_none() => {
+                session.find_in_contents(text)
+            }
+            Query::And(queries) | Query::Or(queries) => {
+                queries.iter().find_map(|q| q.content_match(session))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Query {
+        Query::parse(input).unwrap().unwrap()
+    }
+
+    fn text(word: &str) -> Query {
+        Query::Text(word.to_string())
+    }
+
+    fn session(name: &str) -> FavSessionInfo {
+        FavSessionInfo {
+            name: name.to_string(),
+            is_active: true,
+            tabs: 2,
+            panes: 5,
+            assigned_number: Some(3),
+            tags: vec!["Work".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tokenizes_operators_and_words() {
+        assert_eq!(
+            tokenize("a AND (b|c) -d or e"),
+            vec![
+                Token::Word("a".to_string()),
+                Token::And,
+                Token::Open,
+                Token::Word("b".to_string()),
+                Token::Or,
+                Token::Word("c".to_string()),
+                Token::Close,
+                Token::Not,
+                Token::Word("d".to_string()),
+                Token::Or,
+                Token::Word("e".to_string()),
+            ]
+        );
+        assert_eq!(
+            tokenize("web-api"),
+            vec![Token::Word("web-api".to_string())]
+        );
+    }
+
+    #[test]
+    fn blank_input_is_no_query() {
+        assert_eq!(Query::parse(""), Ok(None));
+        assert_eq!(Query::parse("   "), Ok(None));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a b | c"),
+            Query::Or(vec![Query::And(vec![text("a"), text("b")]), text("c")])
+        );
+        assert_eq!(
+            parse("a and b or c and d"),
+            Query::Or(vec![
+                Query::And(vec![text("a"), text("b")]),
+                Query::And(vec![text("c"), text("d")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn not_applies_to_the_next_term_or_group() {
+        assert_eq!(
+            parse("-a b"),
+            Query::And(vec![Query::Not(Box::new(text("a"))), text("b")])
+        );
+        assert_eq!(
+            parse("-(a | b)"),
+            Query::Not(Box::new(Query::Or(vec![text("a"), text("b")])))
+        );
+    }
+
+    #[test]
+    fn parentheses_group_terms() {
+        assert_eq!(
+            parse("a (b | c)"),
+            Query::And(vec![text("a"), Query::Or(vec![text("b"), text("c")])])
+        );
+    }
+
+    #[test]
+    fn parses_qualifiers() {
+        assert_eq!(parse("#Work"), Query::Tag("work".to_string()));
+        assert_eq!(parse("active:"), Query::Active(true));
+        assert_eq!(parse("dead:"), Query::Active(false));
+        assert_eq!(parse("active:no"), Query::Active(false));
+        assert_eq!(parse("num:"), Query::Number(None));
+        assert_eq!(parse("num:3"), Query::Number(Some(3)));
+        assert_eq!(
+            parse("tabs>2"),
+            Query::Count(Counter::Tabs, Comparison::Greater, 2)
+        );
+        assert_eq!(
+            parse("panes<=4"),
+            Query::Count(Counter::Panes, Comparison::LessOrEqual, 4)
+        );
+        assert_eq!(
+            parse("tabs:1"),
+            Query::Count(Counter::Tabs, Comparison::Equal, 1)
+        );
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let error = |input| Query::parse(input).unwrap_err();
+        assert_eq!(error("#"), "missing tag name after '#'");
+        assert_eq!(error("tabs>x"), "expected a number after 'tabs>'");
+        assert_eq!(error("num:x"), "invalid quick access number 'x'");
+        assert_eq!(error("foo:bar"), "unknown qualifier 'foo:'");
+        assert_eq!(error("active:maybe"), "expected yes or no, found 'maybe'");
+        assert_eq!(error("(a b"), "missing ')'");
+        assert_eq!(error("a b)"), "unexpected ')'");
+        assert_eq!(error("| a"), "expected a term before operator");
+        assert_eq!(error("a or"), "unexpected end of query");
+        assert_eq!(error("-"), "unexpected end of query");
+    }
+
+    #[test]
+    fn matches_sessions() {
+        let session = session("web-api");
+        let matches = |input| parse(input).score(&session, false).is_some();
+        assert!(matches("api"));
+        assert!(matches("#work active: num:3"));
+        assert!(matches("tabs=2 panes>4"));
+        assert!(matches("nope | web"));
+        assert!(matches("-dead:"));
+        assert!(!matches("api -#work"));
+        assert!(!matches("num:4 | tabs<2"));
+        assert!(!matches("num:alt-3"));
+    }
+
+    #[test]
+    fn highlights_chars_of_positive_terms() {
+        assert_eq!(parse("api").highlight("wéb-api"), vec![4, 5, 6]);
+        assert_eq!(parse("w -api").highlight("web-api"), vec![0]);
+        assert_eq!(parse("w | b").highlight("web"), vec![0, 2]);
+        assert!(parse("#work").highlight("web").is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+use zellij_tile::{
+    prelude::PipeMessage,
+    shim::{close_focus, close_self},
+};
+
+use crate::{commands::FavsCommand, favs::Favs, pipe::run_pipe_message, status::Severity};
+
+/// Work that has to see what other instances saved. Each instance keeps its own copy of the
+/// cache, so it is read again first.
+pub enum Deferred {
+    Pipe(PipeMessage),
+    /// A command typed in the palette, with the sessions it targets when it leaves them out.
+    Command(String, Vec<String>),
+    Switch(SwitchTarget),
+    /// Saves the UI state once it stopped changing.
+    SaveUi,
+    Close,
+}
+
+pub enum SwitchTarget {
+    Session(String),
+    Back,
+    Cycle(bool),
+}
+
+/// Runs `deferred` once the cache has been read again. Work queued while a read is on its way
+/// waits for that read.
+pub fn defer(ctx: &mut Favs, deferred: Deferred) {
+    let reading = !ctx.has_loaded || !ctx.deferred.is_empty();
+    ctx.deferred.push(deferred);
+    if !reading {
+        ctx.load_cache();
+    }
+}
+
+pub fn run_deferred(ctx: &mut Favs) {
+    for deferred in std::mem::take(&mut ctx.deferred) {
+        match deferred {
+            Deferred::Pipe(pipe_message) => run_pipe_message(ctx, pipe_message),
+            Deferred::Command(input, targets) => {
+                match FavsCommand::parse(&input).and_then(|command| command.run(ctx, &targets)) {
+                    Ok(output) => {
+                        ctx.notify(Severity::Success, output.trim_end().replace('\n', "  "))
+                    }
+                    Err(e) => ctx.notify(Severity::Error, e),
+                }
+            }
+            Deferred::Switch(target) => {
+                let switched = match target {
+                    SwitchTarget::Session(name) => {
+                        ctx.switch_to(&name);
+                        Ok(name)
+                    }
+                    SwitchTarget::Back => ctx.go_back(),
+                    SwitchTarget::Cycle(forward) => ctx.cycle_favs(forward),
+                };
+                match switched {
+                    Ok(_) => close_focus(),
+                    Err(e) => ctx.notify(Severity::Warning, e),
+                }
+            }
+            Deferred::SaveUi => ctx.save_quietly(),
+            Deferred::Close => {
+                ctx.save_quietly();
+                close_self();
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use zellij_tile::prelude::KeyWithModifier;
+
+use crate::{
+    favs::Favs,
+    favs_mode::FavMode,
+    help::open_help,
+    keymap::{Action, KeyMatch},
+    status::Severity,
+    FavSessionInfo,
+};
+
+/// A known session that disappeared in a session update, likely renamed to one that appeared.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RenameGuess {
+    /// The entry as it was before the update, with all its metadata.
+    pub previous: FavSessionInfo,
+    pub was_fav: bool,
+    /// Position of the entry in its list.
+    pub index: usize,
+    pub new_name: String,
+}
+
+/// Whether two sessions have the same tabs, each with as many panes, and the same number of
+/// connected clients.
+fn same_structure(previous: &FavSessionInfo, session: &FavSessionInfo) -> bool {
+    !previous.tab_list.is_empty()
+        && previous.connected_clients == session.connected_clients
+        && previous.tab_list.len() == session.tab_list.len()
+        && previous
+            .tab_list
+            .iter()
+            .zip(session.tab_list.iter())
+            .all(|(a, b)| a.name == b.name && a.panes.len() == b.panes.len())
+}
+
+/// Pairs the entries that disappeared with the running sessions that appeared in the same
+/// update and look the same. Returns the pairs that only match each other, then the ambiguous
+/// ones, where either side matches several others.
+pub fn detect_renames(
+    gone: &[RenameGuess],
+    appeared: &[FavSessionInfo],
+) -> (Vec<RenameGuess>, Vec<RenameGuess>) {
+    let matches = |guess: &RenameGuess, session: &FavSessionInfo| {
+        session.is_active && same_structure(&guess.previous, session)
+    };
+    let mut certain = vec![];
+    let mut ambiguous = vec![];
+    for guess in gone {
+        let candidates: Vec<&FavSessionInfo> = appeared
+            .iter()
+            .filter(|session| matches(guess, session))
+            .collect();
+        for session in candidates.iter() {
+            let guess = RenameGuess {
+                new_name: session.name.clone(),
+                ..guess.clone()
+            };
+            let rivals = gone.iter().filter(|other| matches(other, session)).count();
+            if candidates.len() == 1 && rivals == 1 {
+                certain.push(guess);
+            } else {
+                ambiguous.push(guess);
+            }
+        }
+    }
+    (certain, ambiguous)
+}
+
+pub fn open_rename_questions(ctx: &mut Favs) {
+    if ctx.rename_questions.is_empty() {
+        ctx.notify(Severity::Info, "no renames to review");
+        return;
+    }
+    ctx.current_column = Some(ctx.mode.clone());
+    ctx.mode = FavMode::Renames;
+}
+
+/// Goes back to the list, leaving the remaining questions for later.
+pub fn close_rename_questions(ctx: &mut Favs) {
+    if ctx.mode == FavMode::Renames {
+        ctx.mode = ctx.current_column.take().unwrap_or(FavMode::NavigateFavs);
+    }
+}
+
+pub fn match_rename_question_keys(ctx: &mut Favs, key: &KeyWithModifier) -> bool {
+    match ctx.resolve_key(key) {
+        KeyMatch::Action(Action::Accept) => ctx.answer_rename_question(true),
+        KeyMatch::Action(Action::Reject) => ctx.answer_rename_question(false),
+        KeyMatch::Action(Action::Cancel) => close_rename_questions(ctx),
+        KeyMatch::Action(Action::Help) => open_help(ctx),
+        _ => return false,
+    }
+    if ctx.rename_questions.is_empty() {
+        close_rename_questions(ctx);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FavPaneInfo, FavTabInfo};
+
+    fn session(name: &str, tabs: &[(&str, usize)]) -> FavSessionInfo {
+        FavSessionInfo {
+            name: name.to_string(),
+            is_active: true,
+            tab_list: tabs
+                .iter()
+                .map(|(tab, panes)| FavTabInfo {
+                    name: tab.to_string(),
+                    panes: vec![FavPaneInfo::default(); *panes],
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn guess(session: FavSessionInfo) -> RenameGuess {
+        RenameGuess {
+            previous: session,
+            was_fav: true,
+            index: 0,
+            new_name: String::new(),
+        }
+    }
+
+    fn pairs(guesses: &[RenameGuess]) -> Vec<(&str, &str)> {
+        guesses
+            .iter()
+            .map(|guess| (guess.previous.name.as_str(), guess.new_name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn pairs_sessions_matching_one_to_one() {
+        let gone = [
+            guess(session("api", &[("editor", 2)])),
+            guess(session("web", &[("editor", 1), ("logs", 1)])),
+        ];
+        let appeared = [
+            session("web-2", &[("editor", 1), ("logs", 1)]),
+            session("api-2", &[("editor", 2)]),
+        ];
+        let (certain, ambiguous) = detect_renames(&gone, &appeared);
+        assert_eq!(pairs(&certain), vec![("api", "api-2"), ("web", "web-2")]);
+        assert!(ambiguous.is_empty());
+    }
+
+    #[test]
+    fn asks_when_several_sessions_look_the_same() {
+        let gone = [guess(session("api", &[("editor", 2)]))];
+        let appeared = [
+            session("one", &[("editor", 2)]),
+            session("two", &[("editor", 2)]),
+        ];
+        let (certain, ambiguous) = detect_renames(&gone, &appeared);
+        assert!(certain.is_empty());
+        assert_eq!(pairs(&ambiguous), vec![("api", "one"), ("api", "two")]);
+
+        let gone = [
+            guess(session("api", &[("editor", 2)])),
+            guess(session("web", &[("editor", 2)])),
+        ];
+        let appeared = [session("new", &[("editor", 2)])];
+        let (certain, ambiguous) = detect_renames(&gone, &appeared);
+        assert!(certain.is_empty());
+        assert_eq!(pairs(&ambiguous), vec![("api", "new"), ("web", "new")]);
+    }
+
+    #[test]
+    fn ignores_sessions_that_look_different() {
+        let gone = [guess(session("api", &[("editor", 2)]))];
+        let appeared = [
+            session("more-panes", &[("editor", 3)]),
+            session("other-tab", &[("shell", 2)]),
+            FavSessionInfo {
+                is_active: false,
+                ..session("dead", &[("editor", 2)])
+            },
+        ];
+        let (certain, ambiguous) = detect_renames(&gone, &appeared);
+        assert!(certain.is_empty());
+        assert!(ambiguous.is_empty());
+    }
+
+    #[test]
+    fn never_guesses_for_sessions_without_known_tabs() {
+        let gone = [guess(session("dead", &[]))];
+        let appeared = [session("new", &[])];
+        assert_eq!(detect_renames(&gone, &appeared), (vec![], vec![]));
+    }
+}
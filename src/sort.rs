@@ -0,0 +1,55 @@
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::FavSessionInfo;
+
+/// Order of the sessions within each list.
+#[derive(Default, Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+pub enum SortMode {
+    /// The order sessions were added to the list in.
+    #[default]
+    Manual,
+    Name,
+    /// Most recently used first.
+    Mru,
+}
+
+impl SortMode {
+    pub fn variants() -> Vec<Self> {
+        vec![SortMode::Manual, SortMode::Name, SortMode::Mru]
+    }
+    pub fn parse(value: &str) -> Option<Self> {
+        SortMode::variants()
+            .into_iter()
+            .find(|mode| mode.to_string() == value.trim())
+    }
+    pub fn sort(&self, sessions: &mut [FavSessionInfo]) {
+        match self {
+            SortMode::Manual => {}
+            SortMode::Name => sessions.sort_by_key(|session| session.name.to_lowercase()),
+            SortMode::Mru => sessions.sort_by_key(|session| std::cmp::Reverse(session.last_used)),
+        }
+    }
+}
+
+impl Display for SortMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortMode::Manual => write!(f, "manual"),
+            SortMode::Name => write!(f, "name"),
+            SortMode::Mru => write!(f, "mru"),
+        }
+    }
+}
+
+/// Seconds since the Unix epoch, used to order sessions by last use.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+use zellij_tile::shim::set_timeout;
+
+use crate::theme::{Line, Role};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn role(&self) -> Option<Role> {
+        match self {
+            Severity::Info => None,
+            Severity::Success => Some(Role::Success),
+            Severity::Warning => Some(Role::Warning),
+            Severity::Error => Some(Role::Error),
+        }
+    }
+    fn timeout(&self) -> Duration {
+        match self {
+            Severity::Warning | Severity::Error => Duration::from_secs(6),
+            Severity::Info | Severity::Success => Duration::from_secs(3),
+        }
+    }
+}
+
+/// A transient message shown in place of the help hint until it expires.
+#[derive(Clone, Debug)]
+pub struct Status {
+    pub message: String,
+    pub severity: Severity,
+    pub expires_at: Instant,
+}
+
+impl Status {
+    /// Creates the status and schedules the timer event that will clear it.
+    pub fn new<S: ToString>(severity: Severity, message: S) -> Self {
+        let timeout = severity.timeout();
+        set_timeout(timeout.as_secs_f64());
+        Status {
+            message: message.to_string(),
+            severity,
+            expires_at: Instant::now() + timeout,
+        }
+    }
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+    pub fn line(&self) -> Line {
+        match self.severity.role() {
+            Some(role) => Line::new().styled(&self.message, role),
+            None => Line::new().plain(&self.message),
+        }
+    }
+}
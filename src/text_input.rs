@@ -0,0 +1,141 @@
+use zellij_tile::prelude::{BareKey, KeyModifier, KeyWithModifier};
+
+/// The character a key types, or `None` for keys held with Ctrl, Alt or Super, which are left
+/// for bindings.
+pub fn typed_char(key: &KeyWithModifier) -> Option<char> {
+    match key.bare_key {
+        BareKey::Char(c) if key.key_modifiers.iter().all(|m| m == &KeyModifier::Shift) => Some(c),
+        _ => None,
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct TextInput {
+    pub value: String,
+    /// Cursor position, in chars.
+    pub cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            cursor: value.chars().count(),
+        }
+    }
+    fn byte_idx(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.value.len())
+    }
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+    pub fn insert(&mut self, text: &str) {
+        let idx = self.byte_idx(self.cursor);
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        self.value.insert_str(idx, &text);
+        self.cursor += text.chars().count();
+    }
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let idx = self.byte_idx(self.cursor);
+        self.value.remove(idx);
+    }
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let idx = self.byte_idx(self.cursor);
+            self.value.remove(idx);
+        }
+    }
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let (from, to) = (self.byte_idx(start), self.byte_idx(self.cursor));
+        self.value.replace_range(from..to, "");
+        self.cursor = start;
+    }
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+    }
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+    pub fn end(&mut self) {
+        self.cursor = self.len();
+    }
+    /// Splits the value around the cursor: text before, char under the cursor, text after.
+    pub fn split_at_cursor(&self) -> (&str, Option<char>, &str) {
+        let idx = self.byte_idx(self.cursor);
+        let (before, rest) = self.value.split_at(idx);
+        let mut chars = rest.chars();
+        let current = chars.next();
+        (before, current, chars.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str, cursor: usize) -> TextInput {
+        TextInput {
+            value: value.to_string(),
+            cursor,
+        }
+    }
+
+    #[test]
+    fn backspace_removes_whole_chars() {
+        let mut input = TextInput::new("añ日");
+        input.backspace();
+        assert_eq!(input, at("añ", 2));
+        input.backspace();
+        assert_eq!(input, at("a", 1));
+
+        let mut input = at("ñé", 1);
+        input.backspace();
+        assert_eq!(input, at("é", 0));
+        input.backspace();
+        assert_eq!(input, at("é", 0));
+    }
+
+    #[test]
+    fn delete_word_stops_at_whitespace() {
+        let mut input = TextInput::new("café  日本語 ");
+        input.delete_word();
+        assert_eq!(input, at("café  ", 6));
+        input.delete_word();
+        assert_eq!(input, at("", 0));
+
+        let mut input = at("über straße", 7);
+        input.delete_word();
+        assert_eq!(input, at("über raße", 5));
+    }
+
+    #[test]
+    fn split_at_cursor_on_multibyte_chars() {
+        assert_eq!(at("日本語", 0).split_at_cursor(), ("", Some('日'), "本語"));
+        assert_eq!(at("日本語", 1).split_at_cursor(), ("日", Some('本'), "語"));
+        assert_eq!(at("日本語", 3).split_at_cursor(), ("日本語", None, ""));
+        assert_eq!(at("", 0).split_at_cursor(), ("", None, ""));
+    }
+}
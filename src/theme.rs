@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use owo_colors::OwoColorize;
+use zellij_tile::shim::{print_text_with_coordinates, Text};
+
+/// What a piece of text means, mapped to a color by the [`Theme`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Role {
+    Prompt,
+    Title,
+    Match,
+    Number,
+    Error,
+    Key,
+    Section,
+    Success,
+    Warning,
+}
+
+impl Role {
+    pub fn variants() -> Vec<Self> {
+        vec![
+            Role::Prompt,
+            Role::Title,
+            Role::Match,
+            Role::Number,
+            Role::Error,
+            Role::Key,
+            Role::Section,
+            Role::Success,
+            Role::Warning,
+        ]
+    }
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            Role::Prompt => "color_prompt",
+            Role::Title => "color_title",
+            Role::Match => "color_match",
+            Role::Number => "color_number",
+            Role::Error => "color_error",
+            Role::Key => "color_key",
+            Role::Section => "color_section",
+            Role::Success => "color_success",
+            Role::Warning => "color_warning",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Color {
+    /// One of the emphasis colors (0-3) of the active Zellij theme.
+    Theme(usize),
+    Rgb(u8, u8, u8),
+    None,
+}
+
+impl TryFrom<&str> for Color {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        if value == "none" {
+            return Ok(Color::None);
+        }
+        if let Ok(level) = value.parse::<usize>() {
+            if level <= 3 {
+                return Ok(Color::Theme(level));
+            }
+        }
+        if let Some(hex) = value.strip_prefix('#').filter(|hex| hex.len() == 6) {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            if let (Ok(r), Ok(g), Ok(b)) = (channel(0), channel(2), channel(4)) {
+                return Ok(Color::Rgb(r, g, b));
+            }
+        }
+        Err(format!("invalid color '{}'", value))
+    }
+}
+
+/// A line of text made of spans, each optionally styled with a [`Role`].
+#[derive(Default, Clone, Debug)]
+pub struct Line {
+    pub spans: Vec<(String, Option<Role>)>,
+    pub selected: bool,
+}
+
+impl Line {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn plain<S: ToString>(mut self, text: S) -> Self {
+        self.spans.push((text.to_string(), None));
+        self
+    }
+    pub fn styled<S: ToString>(mut self, text: S, role: Role) -> Self {
+        self.spans.push((text.to_string(), Some(role)));
+        self
+    }
+    pub fn selected(mut self) -> Self {
+        self.selected = true;
+        self
+    }
+    pub fn append(mut self, other: Line) -> Self {
+        self.spans.extend(other.spans);
+        self
+    }
+    /// Shortens the line to `width` chars, ending it with an ellipsis when it doesn't fit.
+    pub fn truncate(mut self, width: usize) -> Self {
+        if self.len() <= width {
+            return self;
+        }
+        let mut remaining = width.saturating_sub(1);
+        let mut spans = vec![];
+        for (text, role) in self.spans {
+            if remaining == 0 {
+                break;
+            }
+            let text: String = text.chars().take(remaining).collect();
+            remaining -= text.chars().count();
+            spans.push((text, role));
+        }
+        if width > 0 {
+            spans.push(("…".to_string(), None));
+        }
+        self.spans = spans;
+        self
+    }
+    pub fn len(&self) -> usize {
+        self.spans
+            .iter()
+            .map(|(text, _)| text.chars().count())
+            .sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub struct Theme {
+    pub colors: BTreeMap<Role, Color>,
+    pub monochrome: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            colors: BTreeMap::from([
+                (Role::Prompt, Color::Theme(1)),
+                (Role::Title, Color::Theme(2)),
+                (Role::Match, Color::Theme(0)),
+                (Role::Number, Color::Theme(3)),
+                (Role::Error, Color::Theme(0)),
+                (Role::Key, Color::Theme(3)),
+                (Role::Section, Color::Theme(2)),
+                (Role::Success, Color::Theme(2)),
+                (Role::Warning, Color::Theme(1)),
+            ]),
+            monochrome: false,
+        }
+    }
+}
+
+impl Theme {
+    pub fn from_config(configuration: &BTreeMap<String, String>) -> (Self, Vec<String>) {
+        let mut theme = Theme::default();
+        let mut errors = vec![];
+        for role in Role::variants() {
+            let Some(value) = configuration.get(role.config_key()) else {
+                continue;
+            };
+            match Color::try_from(value.as_str()) {
+                Ok(color) => {
+                    theme.colors.insert(role, color);
+                }
+                Err(e) => errors.push(format!("{}: {}", role.config_key(), e)),
+            }
+        }
+        if let Some(monochrome) = configuration.get("monochrome") {
+            theme.monochrome = matches!(monochrome.trim(), "true" | "t" | "y" | "1");
+        }
+        (theme, errors)
+    }
+    fn color(&self, role: Option<Role>) -> Color {
+        role.and_then(|role| self.colors.get(&role).copied())
+            .unwrap_or(Color::None)
+    }
+    /// Turns a line into a Zellij [`Text`], colored through the theme's emphasis levels so it
+    /// follows the user's Zellij theme. Lines using custom RGB colors fall back to ANSI styling.
+    pub fn text(&self, line: &Line) -> Text {
+        let content: String = line.spans.iter().map(|(text, _)| text.as_str()).collect();
+        if self.monochrome {
+            // Without colors the highlighted row is the only way to see the cursor.
+            let text = Text::new(content);
+            return if line.selected { text.selected() } else { text };
+        }
+
+        let uses_rgb = line
+            .spans
+            .iter()
+            .any(|(_, role)| matches!(self.color(*role), Color::Rgb(..)));
+        let mut text = if uses_rgb {
+            let ansi: String = line
+                .spans
+                .iter()
+                .map(|(span, role)| match self.color(*role) {
+                    Color::Rgb(r, g, b) => span.truecolor(r, g, b).to_string(),
+                    Color::Theme(_) => span.bold().to_string(),
+                    Color::None => span.to_string(),
+                })
+                .collect();
+            Text::new(ansi)
+        } else {
+            let mut text = Text::new(content);
+            let mut start = 0;
+            for (span, role) in line.spans.iter() {
+                let end = start + span.chars().count();
+                if let Color::Theme(level) = self.color(*role) {
+                    text = text.color_range(level, start..end);
+                }
+                start = end;
+            }
+            text
+        };
+
+        if line.selected {
+            text = text.selected();
+        }
+        text
+    }
+    pub fn print(&self, line: &Line, x: usize, y: usize, width: Option<usize>) {
+        match width {
+            Some(width) => {
+                let line = line.clone().truncate(width);
+                print_text_with_coordinates(self.text(&line), x, y, Some(width), None);
+            }
+            None => print_text_with_coordinates(self.text(line), x, y, None, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    #[test]
+    fn truncate_keeps_lines_that_fit() {
+        let line = Line::new().plain("ab").styled("cd", Role::Match);
+        assert_eq!(text(&line.clone().truncate(4)), "abcd");
+        assert_eq!(text(&line.truncate(10)), "abcd");
+    }
+
+    #[test]
+    fn truncate_ends_with_an_ellipsis() {
+        let line = Line::new().plain("ab").styled("日本語", Role::Match);
+        let truncated = line.clone().truncate(4);
+        assert_eq!(text(&truncated), "ab日…");
+        assert_eq!(truncated.len(), 4);
+        assert_eq!(truncated.spans[1].1, Some(Role::Match));
+        assert_eq!(text(&line.clone().truncate(3)), "ab…");
+    }
+
+    #[test]
+    fn truncate_to_tiny_widths() {
+        let line = Line::new().plain("abc");
+        assert_eq!(text(&line.clone().truncate(1)), "…");
+        assert_eq!(line.clone().truncate(0).len(), 0);
+        assert_eq!(Line::new().truncate(0).len(), 0);
+    }
+}
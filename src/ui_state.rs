@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    favs::Favs, favs_mode::FavMode, navigate::ColumnCursor, sort::SortMode, text_input::TextInput,
+};
+
+/// How long the UI has to stay unchanged before its state is saved, so moving the cursor
+/// around doesn't write the cache on every key.
+pub const UI_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// What the manager was showing, saved in the cache so reopening the plugin picks up there.
+#[derive(Default, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct UiState {
+    pub column: FavMode,
+    pub fav_cursor: ColumnCursor,
+    pub flush_cursor: ColumnCursor,
+    pub filter: Option<String>,
+    pub sort_mode: SortMode,
+    pub show_details: bool,
+}
+
+impl UiState {
+    pub fn capture(ctx: &Favs) -> Self {
+        UiState {
+            column: ctx.active_column(),
+            fav_cursor: ctx.fav_cursor,
+            flush_cursor: ctx.flush_cursor,
+            filter: ctx
+                .filter
+                .as_ref()
+                .map(|filter| filter.value.clone())
+                .filter(|filter| !filter.is_empty()),
+            sort_mode: ctx.sort_mode,
+            show_details: ctx.show_details,
+        }
+    }
+    /// Puts the state back, unless the user already moved on to another mode.
+    pub fn restore(self, ctx: &mut Favs) {
+        if ctx.mode != FavMode::NavigateFavs {
+            return;
+        }
+        ctx.mode = match self.column {
+            FavMode::NavigateFlush => FavMode::NavigateFlush,
+            _ => FavMode::NavigateFavs,
+        };
+        ctx.fav_cursor = self.fav_cursor;
+        ctx.flush_cursor = self.flush_cursor;
+        ctx.filter = self.filter.as_deref().map(TextInput::new);
+        if !ctx.sort_configured {
+            ctx.sort_mode = self.sort_mode;
+        }
+        ctx.show_details = self.show_details;
+    }
+}
@@ -0,0 +1,29 @@
+use crate::FavSessionInfo;
+
+pub const MAX_UNDO_STEPS: usize = 20;
+
+/// Both lists as they were before a change, put back by undo.
+pub struct UndoStep {
+    pub description: String,
+    pub fav_sessions: Vec<FavSessionInfo>,
+    pub flush_sessions: Vec<FavSessionInfo>,
+    /// What undo can't put back, e.g. deleted sessions.
+    pub note: Option<String>,
+}
+
+/// Puts `previous` back in order, keeping the live state and last use of each session from
+/// `current` and skipping the sessions that no longer exist.
+pub fn restore(previous: &[FavSessionInfo], current: &[FavSessionInfo]) -> Vec<FavSessionInfo> {
+    previous
+        .iter()
+        .filter_map(|previous| {
+            current
+                .iter()
+                .find(|session| session.name == previous.name)
+                .map(|session| FavSessionInfo {
+                    last_used: session.last_used,
+                    ..session.clone().with_metadata(previous)
+                })
+        })
+        .collect()
+}